@@ -1,85 +1,154 @@
-use windows::core::*;
 use windows::Win32::Graphics::Direct2D::Common::D2D1_COLOR_F;
 use super::*;
 
+// Built-in defaults. Settings in the configuration file override them at startup.
+
 macro_rules! tag_keys {
     ($key:expr, $tag:expr) => {
         [
-            Key{mod_key:MOD_KEY,                        key:$key,       func:DwmrApp::view,             arg:Some(Arg{ui: 1 << $tag})},
-            Key{mod_key:MOD_KEY|MOD_CONTROL,            key:$key,       func:DwmrApp::toggle_view,      arg:Some(Arg{ui: 1 << $tag})},
-            Key{mod_key:MOD_KEY|MOD_SHIFT,              key:$key,       func:DwmrApp::tag,              arg:Some(Arg{ui: 1 << $tag})},
-            Key{mod_key:MOD_KEY|MOD_CONTROL|MOD_SHIFT,  key:$key,       func:DwmrApp::toggle_tag,       arg:Some(Arg{ui: 1 << $tag})},
-            Key{mod_key:MOD_CONTROL,                    key:$key,       func:DwmrApp::tag_all,          arg:Some(Arg{ui: 1 << $tag})},
+            Key{mod_key:MOD_KEY,                        key:$key,       func:DwmrApp::view,             arg:Some(Arg{ui: 1 << $tag}), backend:None, action:"view"},
+            Key{mod_key:MOD_KEY|MOD_CONTROL,            key:$key,       func:DwmrApp::toggle_view,      arg:Some(Arg{ui: 1 << $tag}), backend:None, action:"toggle_view"},
+            Key{mod_key:MOD_KEY|MOD_SHIFT,              key:$key,       func:DwmrApp::tag,              arg:Some(Arg{ui: 1 << $tag}), backend:None, action:"tag"},
+            Key{mod_key:MOD_KEY|MOD_CONTROL|MOD_SHIFT,  key:$key,       func:DwmrApp::toggle_tag,       arg:Some(Arg{ui: 1 << $tag}), backend:None, action:"toggle_tag"},
+            Key{mod_key:MOD_CONTROL,                    key:$key,       func:DwmrApp::tag_all,          arg:Some(Arg{ui: 1 << $tag}), backend:None, action:"tag_all"},
         ]
     };
 }
 
-pub const TAGS: [PCWSTR; 9] = [
-    w!("1"),
-    w!("2"),
-    w!("3"),
-    w!("4"),
-    w!("5"),
-    w!("6"),
-    w!("7"),
-    w!("8"),
-    w!("9"),
+macro_rules! exclude_title {
+    ($title:expr) => {
+        ExcludeRule{name: $title.to_string(),   title: Some(Matcher::exact($title)),    ..Default::default()}
+    };
+}
+
+macro_rules! exclude_class {
+    ($class:expr) => {
+        ExcludeRule{name: $class.to_string(),   class: Some(Matcher::exact($class)),    ..Default::default()}
+    };
+}
+
+pub const TAGS: [&str; 9] = [
+    "1",
+    "2",
+    "3",
+    "4",
+    "5",
+    "6",
+    "7",
+    "8",
+    "9",
 ];
 
 pub const DEFAULT_TAGS: u32 = 1 << 0;
 pub const CURRENT_WINDOW_MARK: &str = "*";
 
 pub const BAR_PADDING: f32 = 10.0;
-pub const BAR_FONT: PCWSTR = w!("Arial");
-pub const BAR_UNSELECTED_WINDOW_MARK: PCWSTR = w!("□");
-pub const BAR_SELECTED_WINDOW_MARK: PCWSTR = w!("■");
+pub const BAR_FONT: &str = "Arial";
+pub const BAR_FONT_SIZE: f32 = 15.0;
+pub const BAR_UNSELECTED_WINDOW_MARK: &str = "□";
+pub const BAR_SELECTED_WINDOW_MARK: &str = "■";
+pub const BAR_PASSTHROUGH_MARK: &str = "PASS";
 
 pub const EXCLUDE_DEBUGGED_WINDOW: bool = true;
 
+pub const FLOAT_OWNED_WINDOWS: bool = true;
+pub const DIALOG_CLASSES: [&str; 1] = ["#32770"];
+
+pub const SWALLOW_TERMINALS: [&str; 3] = ["WindowsTerminal.exe", "alacritty.exe", "wezterm-gui.exe"];
+
+pub const SHARED_WORKSPACES: bool = false;
+
+pub const WATCH_CONFIG: bool = false;
+
+pub const DEFAULT_ATTACH: Attach = Attach::Master;
+pub const MONITOR_ATTACH: [(usize, Attach); 0] = [];
+
 pub const MOD_KEY: HOT_KEY_MODIFIERS = MOD_ALT;
 
+// Milliseconds to press the next key of a sequence like "Alt+W, H".
+pub const CHORD_TIMEOUT: u32 = 2000;
+
+pub const KEY_BACKEND: KeyBackend = KeyBackend::Auto;
+
+pub const GENERATED_RULE_FILE: Option<&str> = None;
+
 pub const BAR_TRANSPARENCY: f32 = 0.8;
 
 pub const BAR_COLOR_BACKGROUND      :D2D1_COLOR_F   = D2D1_COLOR_F{ r:  40.0 / 255.0, g:  44.0 / 255.0, b:  55.0 / 255.0, a: 1.0 };
 pub const BAR_COLOR_SELECTED_BOX    :D2D1_COLOR_F   = D2D1_COLOR_F{ r:  43.0 / 255.0, g: 144.0 / 255.0, b: 217.0 / 255.0, a: 1.0 };
 pub const BAR_COLOR_UNSELECTED_TEXT :D2D1_COLOR_F   = D2D1_COLOR_F{ r: 155.0 / 255.0, g: 174.0 / 255.0, b: 200.0 / 255.0, a: 1.0 };
 pub const BAR_COLOR_SELECTED_TEXT   :D2D1_COLOR_F   = D2D1_COLOR_F{ r: 217.0 / 255.0, g: 225.0 / 255.0, b: 232.0 / 255.0, a: 1.0 };
+pub const BAR_COLOR_URGENT_BOX      :D2D1_COLOR_F   = D2D1_COLOR_F{ r: 224.0 / 255.0, g: 108.0 / 255.0, b: 117.0 / 255.0, a: 1.0 };
+
+// Name of the theme built from the colors above.
+pub const THEME: &str = "default";
 
 lazy_static! {
     pub static ref RULES: [Rule; 4] = [
-        Rule{title: None,       class: None,        process_filename: Some("KakaoTalk".to_string()),         is_floating: true,      tags: 1 << 0},
-        Rule{title: Some("화면 속 화면".to_string()),       class: None,        process_filename: None,         is_floating: true,      tags: 1 << 0},
-        Rule{title: None,       class: None,        process_filename: Some("steamapps".to_string()),         is_floating: true,      tags: 1 << 0},
-        Rule{title: None,       class: None,        process_filename: Some("mstsc".to_string()),         is_floating: true,      tags: 1 << 0},
+        Rule{title: None,       class: None,        process_name: Some(Matcher::exact("KakaoTalk.exe").ignore_case()),         is_floating: Some(true),      tags: Some(1 << 0),   ..Default::default()},
+        Rule{title: Some(Matcher::substring("화면 속 화면")),       class: None,        process_name: None,         is_floating: Some(true),      tags: Some(1 << 0),   ..Default::default()},
+        Rule{title: None,       class: None,        process_path: Some(Matcher::substring("\\steamapps\\").ignore_case()),         is_floating: Some(true),      tags: Some(1 << 0),   ..Default::default()},
+        Rule{title: None,       class: None,        process_name: Some(Matcher::exact("mstsc.exe").ignore_case()),         is_floating: Some(true),      tags: Some(1 << 0),   ..Default::default()},
+    ];
+
+    pub static ref EXCLUDE_RULES: [ExcludeRule; 25] = [
+        exclude_title!("Windows Shell Experience Host"),
+        exclude_title!("Microsoft Text Input Application"),
+        exclude_title!("Action center"),
+        exclude_title!("New Notification"),
+        exclude_title!("Date and Time Information"),
+        exclude_title!("Volume Control"),
+        exclude_title!("Network Connections"),
+        exclude_title!("Cortana"),
+        exclude_title!("Start"),
+        exclude_title!("Windows Default Lock Screen"),
+        exclude_title!("Search"),
+        exclude_title!("WinUI Desktop"),
+        exclude_class!("Windows.UI.Core.CoreWindow"),
+        exclude_class!("ForegroundStaging"),
+        exclude_class!("ApplicationManager_DesktopShellWindow"),
+        exclude_class!("Static"),
+        exclude_class!("Scrollbar"),
+        exclude_class!("Progman"),
+        exclude_class!("OleMainThreadWndClass"),
+        exclude_class!("Xaml_WindowedPopupClass"),
+        exclude_class!("LivePreview"),
+        exclude_class!("TaskListOverlayWnd"),
+        exclude_class!("Shell_TrayWnd"),
+        exclude_class!("TopLevelWindowForOverflowXamlIsland"),
+        exclude_class!("TaskManagerWindow"),
     ];
 
     pub static ref TAG_KEYS: [[Key; 5]; 9] = [
-        tag_keys!('1', 0),
-        tag_keys!('2', 1),
-        tag_keys!('3', 2),
-        tag_keys!('4', 3),
-        tag_keys!('5', 4),
-        tag_keys!('6', 5),
-        tag_keys!('7', 6),
-        tag_keys!('8', 7),
-        tag_keys!('9', 8),
+        tag_keys!(VK_1, 0),
+        tag_keys!(VK_2, 1),
+        tag_keys!(VK_3, 2),
+        tag_keys!(VK_4, 3),
+        tag_keys!(VK_5, 4),
+        tag_keys!(VK_6, 5),
+        tag_keys!(VK_7, 6),
+        tag_keys!(VK_8, 7),
+        tag_keys!(VK_9, 8),
     ];
 
-    pub static ref KEYS: [Key; 15] = [
-        Key{mod_key:MOD_KEY,                    key:'Q',     func:DwmrApp::quit,                    arg:None},
-        Key{mod_key:MOD_KEY,                    key:'R',     func:DwmrApp::force_reset,             arg:None},
-        Key{mod_key:MOD_KEY,                    key:'Z',     func:DwmrApp::zoom,                    arg:None},
-        Key{mod_key:MOD_KEY,                    key:'F',     func:DwmrApp::toggle_float,            arg:None},
-        Key{mod_key:MOD_KEY,                    key:'U',     func:DwmrApp::all_unminimize,          arg:None},
-        Key{mod_key:MOD_KEY,                    key:'J',     func:DwmrApp::focus_stack,             arg:Some(Arg{i:  1})},
-        Key{mod_key:MOD_KEY,                    key:'K',     func:DwmrApp::focus_stack,             arg:Some(Arg{i: -1})},
-        Key{mod_key:MOD_KEY,                    key:'T',     func:DwmrApp::set_layout,              arg:Some(Arg{l:  Layout::Tile(Default::default())})},
-        Key{mod_key:MOD_KEY,                    key:'S',     func:DwmrApp::set_layout,              arg:Some(Arg{l:  Layout::Stack(Default::default())})},
-        Key{mod_key:MOD_KEY,                    key:'H',     func:DwmrApp::focus_monitor,           arg:Some(Arg{i:  1})},
-        Key{mod_key:MOD_KEY,                    key:'L',     func:DwmrApp::focus_monitor,           arg:Some(Arg{i: -1})},
-        Key{mod_key:MOD_KEY,                    key:'I',     func:DwmrApp::set_monitor_factor,      arg:Some(Arg{f:  0.05})},
-        Key{mod_key:MOD_KEY,                    key:'D',     func:DwmrApp::set_monitor_factor,      arg:Some(Arg{f: -0.05})},
-        Key{mod_key:MOD_KEY|MOD_SHIFT,          key:'H',     func:DwmrApp::tag_monitor,             arg:Some(Arg{i:  1})},
-        Key{mod_key:MOD_KEY|MOD_SHIFT,          key:'L',     func:DwmrApp::tag_monitor,             arg:Some(Arg{i: -1})},
+    pub static ref KEYS: [Key; 18] = [
+        Key{mod_key:MOD_KEY,                    key:VK_Q,     func:DwmrApp::quit,                    arg:None, backend:None, action:"quit"},
+        Key{mod_key:MOD_KEY,                    key:VK_R,     func:DwmrApp::force_reset,             arg:None, backend:None, action:"force_reset"},
+        Key{mod_key:MOD_KEY|MOD_SHIFT,          key:VK_R,     func:DwmrApp::reload,                  arg:None, backend:None, action:"reload"},
+        Key{mod_key:MOD_KEY,                    key:VK_Z,     func:DwmrApp::zoom,                    arg:None, backend:None, action:"zoom"},
+        Key{mod_key:MOD_KEY,                    key:VK_F,     func:DwmrApp::toggle_float,            arg:None, backend:None, action:"toggle_float"},
+        Key{mod_key:MOD_KEY,                    key:VK_U,     func:DwmrApp::all_unminimize,          arg:None, backend:None, action:"all_unminimize"},
+        Key{mod_key:MOD_KEY|MOD_SHIFT,          key:VK_G,     func:DwmrApp::generate_rule,           arg:None, backend:None, action:"generate_rule"},
+        Key{mod_key:MOD_KEY,                    key:VK_J,     func:DwmrApp::focus_stack,             arg:Some(Arg{i:  1}), backend:None, action:"focus_stack"},
+        Key{mod_key:MOD_KEY,                    key:VK_K,     func:DwmrApp::focus_stack,             arg:Some(Arg{i: -1}), backend:None, action:"focus_stack"},
+        Key{mod_key:MOD_KEY,                    key:VK_A,     func:DwmrApp::focus_urgent,            arg:None, backend:None, action:"focus_urgent"},
+        Key{mod_key:MOD_KEY,                    key:VK_T,     func:DwmrApp::set_layout,              arg:Some(Arg{l:  Layout::Tile(Default::default())}), backend:None, action:"set_layout"},
+        Key{mod_key:MOD_KEY,                    key:VK_S,     func:DwmrApp::set_layout,              arg:Some(Arg{l:  Layout::Stack(Default::default())}), backend:None, action:"set_layout"},
+        Key{mod_key:MOD_KEY,                    key:VK_H,     func:DwmrApp::focus_monitor,           arg:Some(Arg{i:  1}), backend:None, action:"focus_monitor"},
+        Key{mod_key:MOD_KEY,                    key:VK_L,     func:DwmrApp::focus_monitor,           arg:Some(Arg{i: -1}), backend:None, action:"focus_monitor"},
+        Key{mod_key:MOD_KEY,                    key:VK_I,     func:DwmrApp::set_monitor_factor,      arg:Some(Arg{f:  0.05}), backend:None, action:"set_monitor_factor"},
+        Key{mod_key:MOD_KEY,                    key:VK_D,     func:DwmrApp::set_monitor_factor,      arg:Some(Arg{f: -0.05}), backend:None, action:"set_monitor_factor"},
+        Key{mod_key:MOD_KEY|MOD_SHIFT,          key:VK_H,     func:DwmrApp::tag_monitor,             arg:Some(Arg{i:  1}), backend:None, action:"tag_monitor"},
+        Key{mod_key:MOD_KEY|MOD_SHIFT,          key:VK_L,     func:DwmrApp::tag_monitor,             arg:Some(Arg{i: -1}), backend:None, action:"tag_monitor"},
     ];
 }
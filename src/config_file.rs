@@ -0,0 +1,1407 @@
+use windows::Win32::{
+    Graphics::Direct2D::Common::D2D1_COLOR_F,
+    UI::Input::KeyboardAndMouse::*,
+};
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use std::{
+    fmt,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use crate::*;
+
+pub type Action = unsafe fn(&mut DwmrApp, &Option<Arg>) -> Result<()>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    None,
+    Int,
+    Float,
+    Tags,
+    Layout,
+    Mode,
+    Spawn,
+    Macro,
+    Theme,
+}
+
+pub const ACTIONS: [(&str, Action, ArgKind); 27] = [
+    ("quit",                DwmrApp::quit,                  ArgKind::None),
+    ("force_reset",         DwmrApp::force_reset,           ArgKind::None),
+    ("reload",              DwmrApp::reload,                ArgKind::None),
+    ("zoom",                DwmrApp::zoom,                  ArgKind::None),
+    ("toggle_float",        DwmrApp::toggle_float,          ArgKind::None),
+    ("all_unminimize",      DwmrApp::all_unminimize,        ArgKind::None),
+    ("generate_rule",       DwmrApp::generate_rule,         ArgKind::None),
+    ("focus_urgent",        DwmrApp::focus_urgent,          ArgKind::None),
+    ("focus_stack",         DwmrApp::focus_stack,           ArgKind::Int),
+    ("focus_monitor",       DwmrApp::focus_monitor,         ArgKind::Int),
+    ("tag_monitor",         DwmrApp::tag_monitor,           ArgKind::Int),
+    ("set_monitor_factor",  DwmrApp::set_monitor_factor,    ArgKind::Float),
+    ("set_layout",          DwmrApp::set_layout,            ArgKind::Layout),
+    ("view",                DwmrApp::view,                  ArgKind::Tags),
+    ("toggle_view",         DwmrApp::toggle_view,           ArgKind::Tags),
+    ("tag",                 DwmrApp::tag,                   ArgKind::Tags),
+    ("toggle_tag",          DwmrApp::toggle_tag,            ArgKind::Tags),
+    ("tag_all",             DwmrApp::tag_all,               ArgKind::Tags),
+    ("swap_tags",           DwmrApp::swap_tags,             ArgKind::Tags),
+    ("merge_tags",          DwmrApp::merge_tags,            ArgKind::Tags),
+    ("enter_mode",          DwmrApp::enter_mode,            ArgKind::Mode),
+    ("exit_mode",           DwmrApp::exit_mode,             ArgKind::None),
+    ("spawn",               DwmrApp::spawn,                 ArgKind::Spawn),
+    ("run_macro",           DwmrApp::run_macro,             ArgKind::Macro),
+    ("toggle_passthrough",  DwmrApp::toggle_passthrough,    ArgKind::None),
+    ("set_theme",           DwmrApp::set_theme,             ArgKind::Theme),
+    ("cycle_theme",         DwmrApp::cycle_theme,           ArgKind::Int),
+];
+
+pub fn find_action(name: &str) -> Option<(&'static str, Action, ArgKind)> {
+    ACTIONS.iter()
+        .find(|(action_name, _, _)| *action_name == name)
+        .map(|(action_name, action, arg_kind)| (*action_name, *action, *arg_kind))
+}
+
+// The five colors of `[bar.colors]`, spread over the schemes of the default theme.
+#[derive(Debug, Clone, Copy)]
+pub struct BarColors {
+    pub background: D2D1_COLOR_F,
+    pub selected_box: D2D1_COLOR_F,
+    pub unselected_text: D2D1_COLOR_F,
+    pub selected_text: D2D1_COLOR_F,
+    pub urgent_box: D2D1_COLOR_F,
+}
+
+impl Default for BarColors {
+    fn default() -> Self {
+        BarColors {
+            background: BAR_COLOR_BACKGROUND,
+            selected_box: BAR_COLOR_SELECTED_BOX,
+            unselected_text: BAR_COLOR_UNSELECTED_TEXT,
+            selected_text: BAR_COLOR_SELECTED_TEXT,
+            urgent_box: BAR_COLOR_URGENT_BOX,
+        }
+    }
+}
+
+impl BarColors {
+    pub fn to_theme(self, name: &str) -> Theme {
+        let plain = ColorScheme { text: self.unselected_text, background: self.background };
+        let selected = ColorScheme { text: self.selected_text, background: self.selected_box };
+        Theme {
+            name: name.to_string(),
+            normal: plain,
+            selected,
+            occupied: plain,
+            urgent: ColorScheme { text: self.selected_text, background: self.urgent_box },
+            inactive_monitor: ColorScheme { text: self.selected_text, background: self.unselected_text },
+            layout_symbol: plain,
+            status: selected,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ColorScheme {
+    pub text: D2D1_COLOR_F,
+    pub background: D2D1_COLOR_F,
+}
+
+// Colors of the bar. `normal` also fills the bar behind the boxes.
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    pub normal: ColorScheme,
+    pub selected: ColorScheme,
+    // Tags with windows that aren't selected.
+    pub occupied: ColorScheme,
+    pub urgent: ColorScheme,
+    // Selected tags of the monitors without focus.
+    pub inactive_monitor: ColorScheme,
+    pub layout_symbol: ColorScheme,
+    // The active mode and passthrough.
+    pub status: ColorScheme,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        BarColors::default().to_theme(THEME)
+    }
+}
+
+// A keymap that replaces the regular bindings while it is active. Escape or the timeout leaves it.
+#[derive(Clone)]
+pub struct Mode {
+    pub name: String,
+    pub keys: Vec<Key>,
+    // Milliseconds without a key press before leaving the mode, 0 to stay until Escape.
+    pub timeout: u32,
+    // Chord modes are entered by the prefix of a key sequence and left after the next key.
+    pub is_chord: bool,
+}
+
+// Checked against the selected monitor right before the step runs.
+#[derive(Clone, Default)]
+pub struct MacroCondition {
+    layout: Option<Layout>,
+    clients_over: Option<usize>,
+    clients_under: Option<usize>,
+}
+
+impl MacroCondition {
+    pub(crate) fn is_met(&self, layout: &Layout, visible_clients: usize) -> bool {
+        self.layout.is_none_or(|condition_layout| std::mem::discriminant(&condition_layout) == std::mem::discriminant(layout))
+            && self.clients_over.is_none_or(|count| visible_clients > count)
+            && self.clients_under.is_none_or(|count| visible_clients < count)
+    }
+}
+
+#[derive(Clone)]
+pub struct MacroStep {
+    pub func: Action,
+    pub arg: Option<Arg>,
+    pub condition: MacroCondition,
+}
+
+// Actions run in order by one binding.
+#[derive(Clone)]
+pub struct Macro {
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+}
+
+// Bindings suspended while a matching window has focus, so games or remote desktops get the keys.
+#[derive(Debug, Clone)]
+pub struct Passthrough {
+    pub rule: Rule,
+    // `None` suspends every binding.
+    pub keys: Option<Vec<KeySpec>>,
+}
+
+impl Passthrough {
+    pub fn suspends(&self, spec: &KeySpec) -> bool {
+        self.keys.as_ref().is_none_or(|keys| keys.contains(spec))
+    }
+}
+
+#[derive(Clone)]
+pub struct Config {
+    pub tags: Vec<String>,
+    pub default_tags: u32,
+    pub mod_key: HOT_KEY_MODIFIERS,
+    pub exclude_debugged_window: bool,
+    pub shared_workspaces: bool,
+    pub watch_config: bool,
+    pub default_attach: Attach,
+    pub monitor_attach: Vec<(usize, Attach)>,
+    pub float_owned_windows: bool,
+    pub dialog_classes: Vec<String>,
+    pub swallow_terminals: Vec<String>,
+    pub generated_rule_file: Option<String>,
+    pub bar_font: String,
+    pub bar_font_size: f32,
+    pub bar_padding: f32,
+    pub bar_transparency: f32,
+    pub bar_selected_window_mark: String,
+    pub bar_unselected_window_mark: String,
+    pub current_window_mark: String,
+    pub bar_passthrough_mark: String,
+    // The built-in theme comes first.
+    pub themes: Vec<Theme>,
+    // Index into `themes` of the theme used at startup.
+    pub theme: usize,
+    pub rules: Vec<Rule>,
+    pub exclude_rules: Vec<ExcludeRule>,
+    // `KEYS` followed by the tag keys of every tag; a hotkey id is an index into this list.
+    pub keys: Vec<Key>,
+    pub modes: Vec<Mode>,
+    pub chord_timeout: u32,
+    pub key_backend: KeyBackend,
+    // Commands of the `spawn` bindings; a spawn argument is an index into this list.
+    pub spawns: Vec<SpawnCommand>,
+    pub autostart: Vec<Autostart>,
+    pub macros: Vec<Macro>,
+    pub passthrough: Vec<Passthrough>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let keys = KEYS.iter()
+            .chain(TAG_KEYS.iter().flatten())
+            .cloned()
+            .collect();
+
+        Config {
+            tags: TAGS.iter().map(|tag| tag.to_string()).collect(),
+            default_tags: DEFAULT_TAGS,
+            mod_key: MOD_KEY,
+            exclude_debugged_window: EXCLUDE_DEBUGGED_WINDOW,
+            shared_workspaces: SHARED_WORKSPACES,
+            watch_config: WATCH_CONFIG,
+            default_attach: DEFAULT_ATTACH,
+            monitor_attach: MONITOR_ATTACH.to_vec(),
+            float_owned_windows: FLOAT_OWNED_WINDOWS,
+            dialog_classes: DIALOG_CLASSES.iter().map(|class| class.to_string()).collect(),
+            swallow_terminals: SWALLOW_TERMINALS.iter().map(|terminal| terminal.to_string()).collect(),
+            generated_rule_file: GENERATED_RULE_FILE.map(|path| path.to_string()),
+            bar_font: BAR_FONT.to_string(),
+            bar_font_size: BAR_FONT_SIZE,
+            bar_padding: BAR_PADDING,
+            bar_transparency: BAR_TRANSPARENCY,
+            bar_selected_window_mark: BAR_SELECTED_WINDOW_MARK.to_string(),
+            bar_unselected_window_mark: BAR_UNSELECTED_WINDOW_MARK.to_string(),
+            current_window_mark: CURRENT_WINDOW_MARK.to_string(),
+            bar_passthrough_mark: BAR_PASSTHROUGH_MARK.to_string(),
+            themes: vec![Theme::default()],
+            theme: 0,
+            rules: RULES.to_vec(),
+            exclude_rules: EXCLUDE_RULES.to_vec(),
+            keys,
+            modes: Vec::new(),
+            chord_timeout: CHORD_TIMEOUT,
+            key_backend: KEY_BACKEND,
+            spawns: Vec::new(),
+            autostart: Vec::new(),
+            macros: Vec::new(),
+            passthrough: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+    message: String,
+    line: Option<usize>,
+    is_warning: bool,
+}
+
+impl ConfigError {
+    pub fn new(message: impl Into<String>) -> ConfigError {
+        ConfigError { message: message.into(), line: None, is_warning: false }
+    }
+
+    fn warning(message: impl Into<String>) -> ConfigError {
+        ConfigError { is_warning: true, ..Self::new(message) }
+    }
+
+    fn prefixed(self, prefix: &str) -> ConfigError {
+        ConfigError { message: format!("{prefix}: {}", self.message), ..self }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    // Warnings point out likely mistakes but don't stop the configuration from loading.
+    pub fn is_warning(&self) -> bool {
+        self.is_warning
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+// Collects every problem in a file instead of stopping at the first one.
+struct Diagnostics<'a> {
+    text: &'a str,
+    errors: Vec<ConfigError>,
+}
+
+impl Diagnostics<'_> {
+    fn line(&self, span: &Range<usize>) -> usize {
+        self.text[..span.start.min(self.text.len())].matches('\n').count() + 1
+    }
+
+    fn report(&mut self, line: Option<usize>, mut error: ConfigError) {
+        if error.line.is_none() {
+            error.line = line;
+        }
+        self.errors.push(error);
+    }
+
+    fn check<T>(&mut self, span: &Range<usize>, result: std::result::Result<T, ConfigError>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(e) => {
+                self.report(Some(self.line(span)), e);
+                None
+            }
+        }
+    }
+}
+
+lazy_static! {
+    static ref CONFIG: RwLock<Arc<Config>> = RwLock::new(Arc::new(Config::default()));
+    static ref CONFIG_PATH: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+pub fn config() -> Arc<Config> {
+    CONFIG.read().unwrap().clone()
+}
+
+pub fn set_config(config: Config) {
+    *CONFIG.write().unwrap() = Arc::new(config);
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    CONFIG_PATH.read().unwrap().clone()
+}
+
+// %APPDATA%\dwmr-win32\config.toml
+pub fn default_config_path() -> Option<PathBuf> {
+    std::env::var_os("APPDATA").map(|app_data| PathBuf::from(app_data).join("dwmr-win32").join("config.toml"))
+}
+
+pub fn config_modified_time() -> Option<std::time::SystemTime> {
+    config_path().and_then(|path| std::fs::metadata(path).ok()).and_then(|metadata| metadata.modified().ok())
+}
+
+// An explicit path must exist; a missing file at the default location just keeps the built-in defaults.
+pub fn init_config(path: Option<PathBuf>) -> std::result::Result<(), ConfigError> {
+    let is_explicit = path.is_some();
+    let path = match path.or_else(default_config_path) {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+
+    *CONFIG_PATH.write().unwrap() = Some(path.clone());
+    if !is_explicit && !path.exists() {
+        return Ok(());
+    }
+
+    set_config(Config::load(&path)?);
+    Ok(())
+}
+
+impl Config {
+    pub fn load(path: &Path) -> std::result::Result<Config, ConfigError> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ConfigError::new(format!("failed to read {} - {e}", path.display())))?;
+        Self::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> std::result::Result<Config, ConfigError> {
+        let (config, diagnostics) = Self::check(text);
+        match diagnostics.into_iter().find(|diagnostic| !diagnostic.is_warning()) {
+            Some(e) => Err(e),
+            None => Ok(config),
+        }
+    }
+
+    // Every error and warning in the file, with line numbers. Entries with errors are left out of the returned configuration.
+    pub fn check(text: &str) -> (Config, Vec<ConfigError>) {
+        let mut diagnostics = Diagnostics { text, errors: Vec::new() };
+        let config = match toml::from_str::<ConfigFile>(text) {
+            Ok(file) => file.into_config(&mut diagnostics),
+            Err(e) => {
+                let line = e.span().map(|span| diagnostics.line(&span));
+                diagnostics.report(line, ConfigError::new(e.message().trim()));
+                Config::default()
+            }
+        };
+        (config, diagnostics.errors)
+    }
+
+    pub fn tagmask(&self) -> u32 {
+        (1 << self.tags.len()) - 1
+    }
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    tags: Option<Spanned<Vec<String>>>,
+    default_tags: Option<Spanned<Vec<usize>>>,
+    mod_key: Option<Spanned<Vec<String>>>,
+    exclude_debugged_window: Option<bool>,
+    shared_workspaces: Option<bool>,
+    watch_config: Option<bool>,
+    default_attach: Option<Spanned<String>>,
+    monitor_attach: Option<Vec<Spanned<MonitorAttachFile>>>,
+    float_owned_windows: Option<bool>,
+    dialog_classes: Option<Vec<String>>,
+    swallow_terminals: Option<Vec<String>>,
+    generated_rule_file: Option<String>,
+    bar: BarFile,
+    rules: Option<Vec<Spanned<RuleFile>>>,
+    exclude_rules: Option<Vec<Spanned<ExcludeRuleFile>>>,
+    keys: Option<Vec<Spanned<KeyFile>>>,
+    tag_keys: Option<Vec<Spanned<TagKeyFile>>>,
+    modes: Option<Vec<Spanned<ModeFile>>>,
+    chord_timeout: Option<u32>,
+    key_backend: Option<Spanned<String>>,
+    autostart: Option<Vec<Spanned<AutostartFile>>>,
+    macros: Option<Vec<Spanned<MacroFile>>>,
+    passthrough: Option<Vec<Spanned<PassthroughFile>>>,
+    themes: Option<Vec<Spanned<ThemeFile>>>,
+    theme: Option<Spanned<String>>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct BarFile {
+    font: Option<String>,
+    font_size: Option<f32>,
+    padding: Option<f32>,
+    transparency: Option<f32>,
+    selected_window_mark: Option<String>,
+    unselected_window_mark: Option<String>,
+    current_window_mark: Option<String>,
+    passthrough_mark: Option<String>,
+    colors: BarColorsFile,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct BarColorsFile {
+    background: Option<Spanned<String>>,
+    selected_box: Option<Spanned<String>>,
+    unselected_text: Option<Spanned<String>>,
+    selected_text: Option<Spanned<String>>,
+    urgent_box: Option<Spanned<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThemeFile {
+    name: String,
+    normal: Option<ColorSchemeFile>,
+    selected: Option<ColorSchemeFile>,
+    occupied: Option<ColorSchemeFile>,
+    urgent: Option<ColorSchemeFile>,
+    inactive_monitor: Option<ColorSchemeFile>,
+    layout_symbol: Option<ColorSchemeFile>,
+    status: Option<ColorSchemeFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ColorSchemeFile {
+    text: Option<Spanned<String>>,
+    background: Option<Spanned<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MonitorAttachFile {
+    monitor: usize,
+    attach: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MatcherFile {
+    Substring(String),
+    Table(MatcherTable),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MatcherTable {
+    substring: Option<String>,
+    exact: Option<String>,
+    glob: Option<String>,
+    regex: Option<String>,
+    #[serde(default)]
+    ignore_case: bool,
+    #[serde(default)]
+    negate: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum MonitorFile {
+    Index(usize),
+    Name(String),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RectFile {
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    #[serde(default)]
+    percent: bool,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct RuleFile {
+    title: Option<MatcherFile>,
+    class: Option<MatcherFile>,
+    process_name: Option<MatcherFile>,
+    process_path: Option<MatcherFile>,
+    parent_process_name: Option<MatcherFile>,
+    is_floating: Option<bool>,
+    tags: Option<Vec<usize>>,
+    attach: Option<String>,
+    monitor: Option<MonitorFile>,
+    rect: Option<RectFile>,
+    is_centered: Option<bool>,
+    is_sticky: Option<bool>,
+    no_focus: Option<bool>,
+    switch_to_tag: Option<bool>,
+    no_swallow: Option<bool>,
+    reapply_on_title_change: bool,
+    priority: i32,
+    stop: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ExcludeRuleFile {
+    name: String,
+    title: Option<MatcherFile>,
+    class: Option<MatcherFile>,
+    process_name: Option<MatcherFile>,
+    #[serde(default)]
+    style: u32,
+    #[serde(default)]
+    exstyle: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct KeyFile {
+    #[serde(default)]
+    modifiers: Vec<String>,
+    key: String,
+    action: String,
+    arg: Option<toml::Value>,
+    backend: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SpawnFile {
+    Program(String),
+    Table(SpawnTable),
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SpawnTable {
+    program: String,
+    #[serde(default)]
+    args: Vec<String>,
+    cwd: Option<String>,
+    #[serde(default)]
+    env: std::collections::BTreeMap<String, String>,
+    tags: Option<Vec<usize>>,
+    monitor: Option<MonitorFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AutostartFile {
+    program: String,
+    #[serde(default)]
+    args: Vec<String>,
+    cwd: Option<String>,
+    #[serde(default)]
+    env: std::collections::BTreeMap<String, String>,
+    tags: Option<Vec<usize>>,
+    monitor: Option<MonitorFile>,
+    #[serde(default)]
+    on_reload: bool,
+    #[serde(rename = "match")]
+    guard: Option<AutostartMatchFile>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct AutostartMatchFile {
+    title: Option<MatcherFile>,
+    class: Option<MatcherFile>,
+    process_name: Option<MatcherFile>,
+    process_path: Option<MatcherFile>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PassthroughFile {
+    title: Option<MatcherFile>,
+    class: Option<MatcherFile>,
+    process_name: Option<MatcherFile>,
+    process_path: Option<MatcherFile>,
+    keys: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MacroFile {
+    name: String,
+    steps: Vec<Spanned<MacroStepFile>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MacroStepFile {
+    action: String,
+    arg: Option<toml::Value>,
+    if_layout: Option<String>,
+    if_clients_over: Option<usize>,
+    if_clients_under: Option<usize>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ModeFile {
+    name: String,
+    #[serde(default)]
+    timeout: u32,
+    #[serde(default)]
+    keys: Vec<Spanned<KeyFile>>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TagKeyFile {
+    #[serde(default)]
+    modifiers: Vec<String>,
+    action: String,
+    backend: Option<String>,
+}
+
+impl ConfigFile {
+    fn into_config(self, diagnostics: &mut Diagnostics) -> Config {
+        let mut config = Config::default();
+
+        if let Some(tags) = self.tags {
+            let count = tags.get_ref().len();
+            let result = match count {
+                1..=31 => Ok(tags.get_ref().clone()),
+                _ => Err(ConfigError::new(format!("tags: expected 1 to 31 tags, found {count}"))),
+            };
+            if let Some(tags) = diagnostics.check(&tags.span(), result) {
+                config.tags = tags;
+            }
+        }
+        let tags_count = config.tags.len();
+
+        if let Some(default_tags) = self.default_tags {
+            let result = parse_tags(default_tags.get_ref(), tags_count).map_err(|e| e.prefixed("default_tags"));
+            if let Some(default_tags) = diagnostics.check(&default_tags.span(), result) {
+                config.default_tags = default_tags;
+            }
+        }
+
+        let default_mod_key = config.mod_key;
+        let mod_key_line = self.mod_key.as_ref().map(|mod_key| diagnostics.line(&mod_key.span()));
+        if let Some(mod_key) = self.mod_key {
+            let result = parse_modifiers(mod_key.get_ref(), HOT_KEY_MODIFIERS(0)).map_err(|e| e.prefixed("mod_key"));
+            if let Some(mod_key) = diagnostics.check(&mod_key.span(), result) {
+                config.mod_key = mod_key;
+            }
+        }
+
+        set_if_some(&mut config.exclude_debugged_window, self.exclude_debugged_window);
+        set_if_some(&mut config.shared_workspaces, self.shared_workspaces);
+        set_if_some(&mut config.watch_config, self.watch_config);
+        set_if_some(&mut config.float_owned_windows, self.float_owned_windows);
+        set_if_some(&mut config.dialog_classes, self.dialog_classes);
+        set_if_some(&mut config.swallow_terminals, self.swallow_terminals);
+        set_if_some(&mut config.chord_timeout, self.chord_timeout);
+        if let Some(backend) = self.key_backend {
+            let result = backend.get_ref().parse().map_err(ConfigError::new);
+            if let Some(backend) = diagnostics.check(&backend.span(), result) {
+                config.key_backend = backend;
+            }
+        }
+        if self.generated_rule_file.is_some() {
+            config.generated_rule_file = self.generated_rule_file;
+        }
+
+        if let Some(attach) = self.default_attach {
+            let result = parse_attach(attach.get_ref()).map_err(|e| e.prefixed("default_attach"));
+            if let Some(attach) = diagnostics.check(&attach.span(), result) {
+                config.default_attach = attach;
+            }
+        }
+
+        if let Some(monitor_attach) = self.monitor_attach {
+            config.monitor_attach = monitor_attach.into_iter()
+                .filter_map(|monitor_attach| {
+                    let result = parse_attach(&monitor_attach.get_ref().attach).map_err(|e| e.prefixed("monitor_attach"));
+                    diagnostics.check(&monitor_attach.span(), result).map(|attach| (monitor_attach.get_ref().monitor, attach))
+                })
+                .collect();
+        }
+
+        let bar = self.bar;
+        set_if_some(&mut config.bar_font, bar.font);
+        set_if_some(&mut config.bar_font_size, bar.font_size);
+        set_if_some(&mut config.bar_padding, bar.padding);
+        set_if_some(&mut config.bar_transparency, bar.transparency);
+        set_if_some(&mut config.bar_selected_window_mark, bar.selected_window_mark);
+        set_if_some(&mut config.bar_unselected_window_mark, bar.unselected_window_mark);
+        set_if_some(&mut config.current_window_mark, bar.current_window_mark);
+        set_if_some(&mut config.bar_passthrough_mark, bar.passthrough_mark);
+
+        let mut bar_colors = BarColors::default();
+        let colors = [
+            ("background", bar.colors.background, &mut bar_colors.background),
+            ("selected_box", bar.colors.selected_box, &mut bar_colors.selected_box),
+            ("unselected_text", bar.colors.unselected_text, &mut bar_colors.unselected_text),
+            ("selected_text", bar.colors.selected_text, &mut bar_colors.selected_text),
+            ("urgent_box", bar.colors.urgent_box, &mut bar_colors.urgent_box),
+        ];
+        for (name, hex, color) in colors {
+            if let Some(hex) = hex {
+                let result = parse_color(hex.get_ref()).map_err(|e| e.prefixed(&format!("bar.colors.{name}")));
+                if let Some(parsed_color) = diagnostics.check(&hex.span(), result) {
+                    *color = parsed_color;
+                }
+            }
+        }
+        config.themes[0] = bar_colors.to_theme(THEME);
+
+        // A theme starts from the built-in one, and replaces it when it has the same name.
+        for theme_file in self.themes.unwrap_or_default() {
+            let theme = theme_file.into_inner().into_theme(&config.themes[0], diagnostics);
+            match config.themes.iter().position(|existing| existing.name == theme.name) {
+                Some(index) => config.themes[index] = theme,
+                None => config.themes.push(theme),
+            }
+        }
+
+        if let Some(name) = self.theme {
+            let result = config.themes.iter()
+                .position(|theme| theme.name == *name.get_ref())
+                .ok_or_else(|| ConfigError::new(format!("theme: unknown theme {:?}", name.get_ref())));
+            if let Some(index) = diagnostics.check(&name.span(), result) {
+                config.theme = index;
+            }
+        }
+
+        if let Some(rules) = self.rules {
+            let mut rule_lines = Vec::new();
+            config.rules = rules.into_iter()
+                .enumerate()
+                .filter_map(|(index, rule)| {
+                    let span = rule.span();
+                    let rule = diagnostics.check(&span, rule.into_inner().into_rule(tags_count).map_err(|e| e.prefixed(&format!("rules[{index}]"))))?;
+                    rule_lines.push((index, diagnostics.line(&span)));
+                    Some(rule)
+                })
+                .collect();
+            check_shadowed_rules(&config.rules, &rule_lines, diagnostics);
+        }
+
+        if let Some(autostart) = self.autostart {
+            config.autostart = autostart.into_iter()
+                .enumerate()
+                .filter_map(|(index, autostart)| {
+                    let span = autostart.span();
+                    diagnostics.check(&span, autostart.into_inner().into_autostart(tags_count).map_err(|e| e.prefixed(&format!("autostart[{index}]"))))
+                })
+                .collect();
+        }
+
+        if let Some(passthrough) = self.passthrough {
+            config.passthrough = passthrough.into_iter()
+                .enumerate()
+                .filter_map(|(index, passthrough)| {
+                    let span = passthrough.span();
+                    diagnostics.check(&span, passthrough.into_inner().into_passthrough(config.mod_key).map_err(|e| e.prefixed(&format!("passthrough[{index}]"))))
+                })
+                .collect();
+        }
+
+        if let Some(exclude_rules) = self.exclude_rules {
+            config.exclude_rules = exclude_rules.into_iter()
+                .enumerate()
+                .filter_map(|(index, rule)| {
+                    let span = rule.span();
+                    diagnostics.check(&span, rule.into_inner().into_exclude_rule().map_err(|e| e.prefixed(&format!("exclude_rules[{index}]"))))
+                })
+                .collect();
+        }
+
+        // Modes are named before any key is parsed so `enter_mode` can refer to modes declared later.
+        let modes = self.modes.unwrap_or_default();
+        config.modes = modes.iter()
+            .map(|mode| Mode { name: mode.get_ref().name.clone(), keys: Vec::new(), timeout: mode.get_ref().timeout, is_chord: false })
+            .collect();
+
+        if let Some(macros) = self.macros {
+            config.macros = macros.iter()
+                .map(|macro_file| Macro { name: macro_file.get_ref().name.clone(), steps: Vec::new() })
+                .collect();
+            for (macro_index, macro_file) in macros.into_iter().enumerate() {
+                let macro_file = macro_file.into_inner();
+                for (index, step) in macro_file.steps.into_iter().enumerate() {
+                    let span = step.span();
+                    let result = step.into_inner().into_step(&mut config).map_err(|e| e.prefixed(&format!("macros.{}.steps[{index}]", macro_file.name)));
+                    if let Some(step) = diagnostics.check(&span, result) {
+                        config.macros[macro_index].steps.push(step);
+                    }
+                }
+            }
+        }
+
+        // keymaps[0] holds the regular bindings, keymaps[i + 1] the bindings of mode i.
+        let mut keymaps: Vec<Vec<(Key, Option<usize>)>> = vec![Vec::new(); config.modes.len() + 1];
+        let mut chords: Vec<(usize, KeySpec, usize)> = Vec::new();
+
+        // Default bindings are written against `MOD_KEY`, so they follow a configured `mod_key`. They
+        // have no line.
+        match self.keys {
+            Some(keys) => {
+                for (index, key) in keys.into_iter().enumerate() {
+                    let span = key.span();
+                    let result = key.into_inner().into_key(&mut config).map_err(|e| e.prefixed(&format!("keys[{index}]")));
+                    if let Some((sequence, key)) = diagnostics.check(&span, result) {
+                        let line = diagnostics.line(&span);
+                        bind_sequence(&mut config, &mut keymaps, &mut chords, 0, &sequence, key, Some(line));
+                    }
+                }
+            }
+            None => keymaps[0] = KEYS.iter().map(|key| (remap_mod_key(key, default_mod_key, config.mod_key), None)).collect(),
+        }
+
+        for (mode_index, mode) in modes.into_iter().enumerate() {
+            let mode_name = mode.get_ref().name.clone();
+            for (index, key) in mode.into_inner().keys.into_iter().enumerate() {
+                let span = key.span();
+                let result = key.into_inner().into_key(&mut config).map_err(|e| e.prefixed(&format!("modes.{mode_name}.keys[{index}]")));
+                if let Some((sequence, key)) = diagnostics.check(&span, result) {
+                    let line = diagnostics.line(&span);
+                    bind_sequence(&mut config, &mut keymaps, &mut chords, mode_index + 1, &sequence, key, Some(line));
+                }
+            }
+        }
+        let mut keys = std::mem::take(&mut keymaps[0]);
+
+        match self.tag_keys {
+            Some(tag_keys) => {
+                for tag in 0..tags_count.min(9) {
+                    for (index, tag_key) in tag_keys.iter().enumerate() {
+                        let span = tag_key.span();
+                        let result = tag_key.get_ref().to_key(config.mod_key, tag).map_err(|e| e.prefixed(&format!("tag_keys[{index}]")));
+                        // The same mistake would be reported once for every tag.
+                        if tag > 0 && result.is_err() {
+                            continue;
+                        }
+                        if let Some(key) = diagnostics.check(&span, result) {
+                            keys.push((key, Some(diagnostics.line(&span))));
+                        }
+                    }
+                }
+            }
+            None => keys.extend(TAG_KEYS.iter()
+                .take(tags_count)
+                .flatten()
+                .map(|key| (remap_mod_key(key, default_mod_key, config.mod_key), None))),
+        }
+
+        drop_shadowed_defaults(&mut keys, mod_key_line, diagnostics);
+        check_duplicate_keys(&keys, diagnostics);
+        config.keys = keys.into_iter().map(|(key, _)| key).collect();
+        for (mode, keys) in config.modes.iter_mut().zip(keymaps.into_iter().skip(1)) {
+            check_duplicate_keys(&keys, diagnostics);
+            mode.keys = keys.into_iter().map(|(key, _)| key).collect();
+        }
+
+        config
+    }
+}
+
+// "Alt+W, H" binds Alt+W to a chord mode holding H. Sequences sharing a prefix share its chord mode.
+fn bind_sequence(
+    config: &mut Config,
+    keymaps: &mut Vec<Vec<(Key, Option<usize>)>>,
+    chords: &mut Vec<(usize, KeySpec, usize)>,
+    mut keymap: usize,
+    sequence: &[KeySpec],
+    key: Key,
+    line: Option<usize>)
+{
+    for (depth, prefix) in sequence[..sequence.len() - 1].iter().enumerate() {
+        let chord = chords.iter().find(|(chord_keymap, chord_prefix, _)| *chord_keymap == keymap && chord_prefix == prefix);
+        let mode_index = match chord {
+            Some((_, _, mode_index)) => *mode_index,
+            None => {
+                let mode_index = config.modes.len();
+                let name = sequence[..=depth].iter().map(|spec| spec.to_string()).collect::<Vec<_>>().join(", ");
+                config.modes.push(Mode { name, keys: Vec::new(), timeout: config.chord_timeout, is_chord: true });
+                keymaps.push(Vec::new());
+                let prefix_key = Key { mod_key: prefix.modifiers, key: prefix.key, func: DwmrApp::enter_mode, arg: Some(Arg{ui: mode_index as u32}), backend: key.backend, action: "enter_mode" };
+                keymaps[keymap].push((prefix_key, line));
+                chords.push((keymap, *prefix, mode_index));
+                mode_index
+            }
+        };
+        keymap = mode_index + 1;
+    }
+    keymaps[keymap].push((key, line));
+}
+
+// Two bindings of the same hotkey make `RegisterHotKey` fail.
+// A default binding gives way to the user's bindings, and to an earlier default once `mod_key` remaps
+// them onto the same key, so only the user's own entries can collide. Each dropped default is reported
+// as a warning, on the line of the binding that replaced it or else on the `mod_key` line.
+fn drop_shadowed_defaults(keys: &mut Vec<(Key, Option<usize>)>, mod_key_line: Option<usize>, diagnostics: &mut Diagnostics) {
+    let mut bound: Vec<(Key, Option<usize>)> = keys.iter()
+        .filter(|(_, line)| line.is_some())
+        .cloned()
+        .collect();
+    keys.retain(|(key, line)| {
+        if line.is_some() {
+            return true;
+        }
+
+        let replacing = bound.iter().find(|(other, _)| other.spec() == key.spec());
+        if let Some((other, other_line)) = replacing {
+            let message = match other_line {
+                Some(other_line) => format!("the default {} binding on {} is replaced by {} on line {other_line}", key.action, key.spec(), other.action),
+                None => format!("the default {} binding on {} is replaced by the default {} binding", key.action, key.spec(), other.action),
+            };
+            diagnostics.report(other_line.or(mod_key_line), ConfigError::warning(message));
+            return false;
+        }
+        bound.push((key.clone(), None));
+        true
+    });
+}
+
+fn check_duplicate_keys(keys: &[(Key, Option<usize>)], diagnostics: &mut Diagnostics) {
+    for (index, (key, line)) in keys.iter().enumerate() {
+        let duplicate = keys[..index].iter().find(|(other, _)| other.mod_key == key.mod_key && other.key == key.key);
+        if let Some((_, other_line)) = duplicate {
+            let message = match other_line {
+                Some(other_line) => format!("{} is already bound on line {other_line}", key.spec()),
+                None => format!("{} is already bound by a default key", key.spec()),
+            };
+            diagnostics.report(*line, ConfigError::new(message));
+        }
+    }
+}
+
+// A rule is never applied when a rule that skips it with `stop` matches every window it matches.
+fn check_shadowed_rules(rules: &[Rule], rule_lines: &[(usize, usize)], diagnostics: &mut Diagnostics) {
+    for (index, rule) in rules.iter().enumerate() {
+        let shadowing = (0..rules.len()).find(|other| rules[*other].skips(*other, rule, index) && rules[*other].covers(rule));
+        if let Some(shadowing) = shadowing {
+            let (rule_index, line) = rule_lines[index];
+            let (shadowing_index, shadowing_line) = rule_lines[shadowing];
+            diagnostics.report(Some(line), ConfigError::warning(format!(
+                "rules[{rule_index}] is never applied, rules[{shadowing_index}] on line {shadowing_line} matches the same windows and stops")));
+        }
+    }
+}
+
+impl MatcherFile {
+    fn into_matcher(self) -> std::result::Result<Matcher, ConfigError> {
+        let table = match self {
+            MatcherFile::Substring(pattern) => return Ok(Matcher::substring(&pattern)),
+            MatcherFile::Table(table) => table,
+        };
+
+        let patterns = [
+            (MatchKind::Substring, table.substring),
+            (MatchKind::Exact, table.exact),
+            (MatchKind::Glob, table.glob),
+            (MatchKind::Regex, table.regex),
+        ];
+        let mut patterns = patterns.into_iter().filter_map(|(kind, pattern)| pattern.map(|pattern| (kind, pattern)));
+        let (kind, pattern) = match (patterns.next(), patterns.next()) {
+            (Some(pattern), None) => pattern,
+            _ => return Err(ConfigError::new("a matcher needs exactly one of substring, exact, glob or regex")),
+        };
+
+        Matcher::new(kind, &pattern, table.ignore_case, table.negate)
+            .map_err(|e| ConfigError::new(format!("invalid regex {pattern:?} - {e}")))
+    }
+}
+
+fn into_matcher(matcher: Option<MatcherFile>, field: &str) -> std::result::Result<Option<Matcher>, ConfigError> {
+    matcher.map(|matcher| matcher.into_matcher())
+        .transpose()
+        .map_err(|e| e.prefixed(field))
+}
+
+impl RuleFile {
+    fn into_rule(self, tags_count: usize) -> std::result::Result<Rule, ConfigError> {
+        let tags = self.tags
+            .map(|tags| parse_tags(&tags, tags_count))
+            .transpose()
+            .map_err(|e| e.prefixed("tags"))?;
+        let attach = self.attach
+            .map(|attach| parse_attach(&attach))
+            .transpose()
+            .map_err(|e| e.prefixed("attach"))?;
+        let monitor = self.monitor.map(MonitorFile::into_target);
+        let rect = self.rect.map(|rect| match rect.percent {
+            true => RuleRect::Percent{x: rect.x, y: rect.y, width: rect.width, height: rect.height},
+            false => RuleRect::Absolute{x: rect.x as i32, y: rect.y as i32, width: rect.width as i32, height: rect.height as i32},
+        });
+
+        Ok(Rule {
+            title: into_matcher(self.title, "title")?,
+            class: into_matcher(self.class, "class")?,
+            process_name: into_matcher(self.process_name, "process_name")?,
+            process_path: into_matcher(self.process_path, "process_path")?,
+            parent_process_name: into_matcher(self.parent_process_name, "parent_process_name")?,
+            is_floating: self.is_floating,
+            tags,
+            attach,
+            monitor,
+            rect,
+            is_centered: self.is_centered,
+            is_sticky: self.is_sticky,
+            no_focus: self.no_focus,
+            switch_to_tag: self.switch_to_tag,
+            no_swallow: self.no_swallow,
+            reapply_on_title_change: self.reapply_on_title_change,
+            priority: self.priority,
+            stop: self.stop,
+        })
+    }
+}
+
+impl ExcludeRuleFile {
+    fn into_exclude_rule(self) -> std::result::Result<ExcludeRule, ConfigError> {
+        let rule = ExcludeRule {
+            title: into_matcher(self.title, "title")?,
+            class: into_matcher(self.class, "class")?,
+            process_name: into_matcher(self.process_name, "process_name")?,
+            name: self.name,
+            style: self.style,
+            exstyle: self.exstyle,
+        };
+        // Without a matcher every window would match.
+        if rule.title.is_none() && rule.class.is_none() && rule.process_name.is_none() && rule.style == 0 && rule.exstyle == 0 {
+            return Err(ConfigError::new("expected title, class, process_name, style or exstyle"));
+        }
+        Ok(rule)
+    }
+}
+
+impl KeyFile {
+    // The keys pressed one after another, and the binding of the last one. `modifiers` apply to the first key.
+    fn into_key(self, config: &mut Config) -> std::result::Result<(Vec<KeySpec>, Key), ConfigError> {
+        let (action, func, arg_kind) = find_action(&self.action)
+            .ok_or_else(|| ConfigError::new(format!("unknown action {:?}", self.action)))?;
+
+        let mut sequence = self.key.split(',')
+            .map(|spec| KeySpec::parse(spec, config.mod_key))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(ConfigError::new)?;
+        sequence[0].modifiers |= parse_modifiers(&self.modifiers, config.mod_key)?;
+
+        let spec = sequence[sequence.len() - 1];
+        let key = Key {
+            mod_key: spec.modifiers,
+            key: spec.key,
+            func,
+            arg: parse_arg(arg_kind, self.arg.as_ref(), config)?,
+            backend: parse_backend(self.backend.as_deref())?,
+            action,
+        };
+        Ok((sequence, key))
+    }
+}
+
+impl SpawnFile {
+    fn into_command(self, tags_count: usize) -> std::result::Result<SpawnCommand, ConfigError> {
+        let table = match self {
+            SpawnFile::Program(program) => return Ok(SpawnCommand { program, ..Default::default() }),
+            SpawnFile::Table(table) => table,
+        };
+
+        Ok(SpawnCommand {
+            program: table.program,
+            args: table.args,
+            cwd: table.cwd,
+            env: table.env.into_iter().collect(),
+            tags: table.tags.map(|tags| parse_tags(&tags, tags_count)).transpose().map_err(|e| e.prefixed("tags"))?,
+            monitor: table.monitor.map(MonitorFile::into_target),
+        })
+    }
+}
+
+impl AutostartFile {
+    fn into_autostart(self, tags_count: usize) -> std::result::Result<Autostart, ConfigError> {
+        let command = SpawnFile::Table(SpawnTable {
+            program: self.program,
+            args: self.args,
+            cwd: self.cwd,
+            env: self.env,
+            tags: self.tags,
+            monitor: self.monitor,
+        }).into_command(tags_count)?;
+
+        let check_process = self.guard.is_none();
+        let guard = match self.guard {
+            Some(guard) => Rule {
+                title: into_matcher(guard.title, "match.title")?,
+                class: into_matcher(guard.class, "match.class")?,
+                process_name: into_matcher(guard.process_name, "match.process_name")?,
+                process_path: into_matcher(guard.process_path, "match.process_path")?,
+                ..Default::default()
+            },
+            None => Rule {
+                process_name: Some(Matcher::exact(&command.exe_name()).ignore_case()),
+                ..Default::default()
+            },
+        };
+        // Without a matcher every window would match.
+        if guard.title.is_none() && guard.class.is_none() && guard.process_name.is_none() && guard.process_path.is_none() {
+            return Err(ConfigError::new("match: expected title, class, process_name or process_path"));
+        }
+
+        Ok(Autostart { command, on_reload: self.on_reload, guard, check_process })
+    }
+}
+
+impl ThemeFile {
+    fn into_theme(self, base: &Theme, diagnostics: &mut Diagnostics) -> Theme {
+        let mut theme = Theme { name: self.name.clone(), ..base.clone() };
+        let schemes = [
+            ("normal", self.normal, &mut theme.normal),
+            ("selected", self.selected, &mut theme.selected),
+            ("occupied", self.occupied, &mut theme.occupied),
+            ("urgent", self.urgent, &mut theme.urgent),
+            ("inactive_monitor", self.inactive_monitor, &mut theme.inactive_monitor),
+            ("layout_symbol", self.layout_symbol, &mut theme.layout_symbol),
+            ("status", self.status, &mut theme.status),
+        ];
+        for (scheme_name, scheme_file, scheme) in schemes {
+            let Some(scheme_file) = scheme_file else {
+                continue;
+            };
+
+            for (color_name, hex, color) in [("text", scheme_file.text, &mut scheme.text), ("background", scheme_file.background, &mut scheme.background)] {
+                if let Some(hex) = hex {
+                    let result = parse_color(hex.get_ref()).map_err(|e| e.prefixed(&format!("themes.{}.{scheme_name}.{color_name}", self.name)));
+                    if let Some(parsed_color) = diagnostics.check(&hex.span(), result) {
+                        *color = parsed_color;
+                    }
+                }
+            }
+        }
+        theme
+    }
+}
+
+impl PassthroughFile {
+    fn into_passthrough(self, mod_key: HOT_KEY_MODIFIERS) -> std::result::Result<Passthrough, ConfigError> {
+        let rule = Rule {
+            title: into_matcher(self.title, "title")?,
+            class: into_matcher(self.class, "class")?,
+            process_name: into_matcher(self.process_name, "process_name")?,
+            process_path: into_matcher(self.process_path, "process_path")?,
+            ..Default::default()
+        };
+        // Without a matcher every window would match.
+        if rule.title.is_none() && rule.class.is_none() && rule.process_name.is_none() && rule.process_path.is_none() {
+            return Err(ConfigError::new("expected title, class, process_name or process_path"));
+        }
+
+        let keys = self.keys
+            .map(|keys| keys.iter().map(|spec| KeySpec::parse(spec, mod_key)).collect::<std::result::Result<Vec<_>, _>>())
+            .transpose()
+            .map_err(|e| ConfigError::new(e).prefixed("keys"))?;
+        Ok(Passthrough { rule, keys })
+    }
+}
+
+impl MacroStepFile {
+    fn into_step(self, config: &mut Config) -> std::result::Result<MacroStep, ConfigError> {
+        let (_, func, arg_kind) = find_action(&self.action)
+            .ok_or_else(|| ConfigError::new(format!("unknown action {:?}", self.action)))?;
+        if arg_kind == ArgKind::Macro {
+            return Err(ConfigError::new("a macro can't run another macro"));
+        }
+
+        let condition = MacroCondition {
+            layout: self.if_layout.map(|layout| parse_layout(&layout)).transpose().map_err(|e| e.prefixed("if_layout"))?,
+            clients_over: self.if_clients_over,
+            clients_under: self.if_clients_under,
+        };
+
+        Ok(MacroStep {
+            func,
+            arg: parse_arg(arg_kind, self.arg.as_ref(), config)?,
+            condition,
+        })
+    }
+}
+
+impl MonitorFile {
+    fn into_target(self) -> MonitorTarget {
+        match self {
+            MonitorFile::Index(index) => MonitorTarget::Index(index),
+            MonitorFile::Name(name) => MonitorTarget::Name(name),
+        }
+    }
+}
+
+impl TagKeyFile {
+    fn to_key(&self, mod_key: HOT_KEY_MODIFIERS, tag: usize) -> std::result::Result<Key, ConfigError> {
+        let (action, func, arg_kind) = find_action(&self.action)
+            .ok_or_else(|| ConfigError::new(format!("unknown action {:?}", self.action)))?;
+        if arg_kind != ArgKind::Tags {
+            return Err(ConfigError::new(format!("action {:?} doesn't take a tag", self.action)));
+        }
+
+        Ok(Key {
+            mod_key: parse_modifiers(&self.modifiers, mod_key)?,
+            key: VIRTUAL_KEY(VK_1.0 + tag as u16),
+            func,
+            arg: Some(Arg{ui: 1 << tag}),
+            backend: parse_backend(self.backend.as_deref())?,
+            action,
+        })
+    }
+}
+
+fn parse_backend(backend: Option<&str>) -> std::result::Result<Option<KeyBackend>, ConfigError> {
+    backend.map(|backend| backend.parse()).transpose().map_err(ConfigError::new)
+}
+
+fn set_if_some<T>(target: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *target = value;
+    }
+}
+
+fn remap_mod_key(key: &Key, from: HOT_KEY_MODIFIERS, to: HOT_KEY_MODIFIERS) -> Key {
+    let mut key = key.clone();
+    if from != to && (key.mod_key & from) == from {
+        key.mod_key = (key.mod_key & !from) | to;
+    }
+    key
+}
+
+// "Mod" stands for the configured `mod_key`.
+fn parse_modifiers(modifiers: &[String], mod_key: HOT_KEY_MODIFIERS) -> std::result::Result<HOT_KEY_MODIFIERS, ConfigError> {
+    let mut result = HOT_KEY_MODIFIERS(0);
+    for modifier in modifiers.iter() {
+        result |= parse_modifier(modifier, mod_key).ok_or_else(|| ConfigError::new(format!("unknown modifier {modifier:?}")))?;
+    }
+    Ok(result)
+}
+
+fn parse_arg(arg_kind: ArgKind, value: Option<&toml::Value>, config: &mut Config) -> std::result::Result<Option<Arg>, ConfigError> {
+    let tags_count = config.tags.len();
+    let value = match (arg_kind, value) {
+        (ArgKind::None, None) => return Ok(None),
+        (ArgKind::None, Some(_)) => return Err(ConfigError::new("this action doesn't take an argument")),
+        (_, None) => return Err(ConfigError::new("missing argument")),
+        (_, Some(value)) => value,
+    };
+
+    let arg = match (arg_kind, value) {
+        (ArgKind::Int, toml::Value::Integer(i)) => Arg{i: *i as i32},
+        (ArgKind::Float, toml::Value::Float(f)) => Arg{f: *f as f32},
+        (ArgKind::Float, toml::Value::Integer(i)) => Arg{f: *i as f32},
+        (ArgKind::Tags, toml::Value::Integer(tag)) => Arg{ui: parse_tags(&[*tag as usize], tags_count)?},
+        (ArgKind::Tags, toml::Value::Array(tags)) => {
+            let tags = tags.iter()
+                .map(|tag| tag.as_integer().map(|tag| tag as usize).ok_or_else(|| ConfigError::new("tags must be numbers")))
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            Arg{ui: parse_tags(&tags, tags_count)?}
+        }
+        (ArgKind::Layout, toml::Value::String(layout)) => Arg{l: parse_layout(layout)?},
+        (ArgKind::Mode, toml::Value::String(name)) => {
+            let mode_index = config.modes.iter()
+                .position(|mode| mode.name == *name)
+                .ok_or_else(|| ConfigError::new(format!("unknown mode {name:?}")))?;
+            Arg{ui: mode_index as u32}
+        }
+        (ArgKind::Spawn, toml::Value::String(_) | toml::Value::Table(_)) => {
+            let spawn = value.clone().try_into::<SpawnFile>()
+                .map_err(|e| ConfigError::new(format!("invalid spawn command - {}", e.message().trim())))?;
+            config.spawns.push(spawn.into_command(tags_count)?);
+            Arg{ui: (config.spawns.len() - 1) as u32}
+        }
+        (ArgKind::Macro, toml::Value::String(name)) => {
+            let macro_index = config.macros.iter()
+                .position(|macro_entry| macro_entry.name == *name)
+                .ok_or_else(|| ConfigError::new(format!("unknown macro {name:?}")))?;
+            Arg{ui: macro_index as u32}
+        }
+        (ArgKind::Theme, toml::Value::String(name)) => {
+            let theme_index = config.themes.iter()
+                .position(|theme| theme.name == *name)
+                .ok_or_else(|| ConfigError::new(format!("unknown theme {name:?}")))?;
+            Arg{ui: theme_index as u32}
+        }
+        _ => return Err(ConfigError::new(format!("invalid argument {value}, expected {}", match arg_kind {
+            ArgKind::Int => "an integer",
+            ArgKind::Float => "a number",
+            ArgKind::Tags => "a tag number or a list of tag numbers",
+            ArgKind::Mode => "a mode name",
+            ArgKind::Macro => "a macro name",
+            ArgKind::Theme => "a theme name",
+            ArgKind::Spawn => "a program or a table with program, args, cwd, env, tags and monitor",
+            _ => "\"tile\" or \"stack\"",
+        }))),
+    };
+    Ok(Some(arg))
+}
+
+// Tags are numbered from 1 in the configuration file.
+fn parse_tags(tags: &[usize], tags_count: usize) -> std::result::Result<u32, ConfigError> {
+    let mut mask = 0;
+    for tag in tags.iter() {
+        if *tag == 0 || *tag > tags_count {
+            return Err(ConfigError::new(format!("tag {tag} is out of range 1..={tags_count}")));
+        }
+        mask |= 1 << (tag - 1);
+    }
+    Ok(mask)
+}
+
+fn parse_attach(attach: &str) -> std::result::Result<Attach, ConfigError> {
+    match attach.to_lowercase().as_str() {
+        "master" => Ok(Attach::Master),
+        "aside" => Ok(Attach::Aside),
+        "below" => Ok(Attach::Below),
+        "above" => Ok(Attach::Above),
+        "bottom" => Ok(Attach::Bottom),
+        _ => Err(ConfigError::new(format!("unknown attach position {attach:?}"))),
+    }
+}
+
+fn parse_layout(layout: &str) -> std::result::Result<Layout, ConfigError> {
+    match layout.to_lowercase().as_str() {
+        "tile" => Ok(Layout::Tile(Default::default())),
+        "stack" => Ok(Layout::Stack(Default::default())),
+        _ => Err(ConfigError::new(format!("unknown layout {layout:?}"))),
+    }
+}
+
+// "#rrggbb" or "#rrggbbaa"
+pub fn parse_color(hex: &str) -> std::result::Result<D2D1_COLOR_F, ConfigError> {
+    let digits = hex.strip_prefix('#').unwrap_or(hex);
+    if !(digits.len() == 6 || digits.len() == 8) || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(ConfigError::new(format!("invalid color {hex:?}, expected #rrggbb or #rrggbbaa")));
+    }
+
+    let channel = |index: usize| u8::from_str_radix(&digits[index * 2..index * 2 + 2], 16).unwrap() as f32 / 255.0;
+    Ok(D2D1_COLOR_F {
+        r: channel(0),
+        g: channel(1),
+        b: channel(2),
+        a: if digits.len() == 8 { channel(3) } else { 1.0 },
+    })
+}
@@ -7,7 +7,7 @@ use windows::{
     }
 };
 
-use crate::BAR_FONT;
+use crate::config_file::config;
 
 const FHD_WIDTH: f32 = 1920.0;
 
@@ -27,6 +27,7 @@ pub unsafe fn implement_draw_text_box(
     box_brush: &ID2D1SolidColorBrush,
     text_brush: &ID2D1SolidColorBrush,
 ) -> Result<f32> {
+    let font = HSTRING::from(&config().bar_font);
     let text_range = DWRITE_TEXT_RANGE{startPosition: 0, length: text.len() as u32};
     let mut text_metrics = DWRITE_TEXT_METRICS::default();
 
@@ -36,7 +37,7 @@ pub unsafe fn implement_draw_text_box(
         max_width / dpi, 
         max_height / dpi)?;
     text_layout.SetFontSize(font_size, text_range.clone())?;
-    text_layout.SetFontFamilyName(BAR_FONT, text_range.clone())?;
+    text_layout.SetFontFamilyName(&font, text_range.clone())?;
     text_layout.SetLocaleName(w!("ko_kr"), text_range.clone())?;
     text_layout.GetMetrics(&mut text_metrics)?;
 
@@ -71,7 +72,7 @@ pub unsafe fn implement_draw_text_box(
             max_width / dpi, 
             max_height / dpi)?;
         super_text_layout.SetFontSize(font_size * 0.8, super_text_range.clone())?;
-        super_text_layout.SetFontFamilyName(BAR_FONT, super_text_range.clone())?;
+        super_text_layout.SetFontFamilyName(&font, super_text_range.clone())?;
         super_text_layout.SetLocaleName(w!("ko-kr"), super_text_range.clone())?;
         super_text_layout.GetMetrics(&mut super_text_metrics)?;
 
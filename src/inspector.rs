@@ -0,0 +1,117 @@
+use windows::{
+    core::*,
+    Win32::{
+        Foundation::*,
+        UI::WindowsAndMessaging::*,
+    }
+};
+
+use serde::Serialize;
+
+use crate::*;
+
+#[derive(Default, Clone, Debug, Serialize)]
+pub struct WindowReport {
+    pub hwnd: isize,
+    pub title: String,
+    pub class: String,
+    pub process_name: String,
+    pub process_id: u32,
+    pub style: u32,
+    pub exstyle: u32,
+    pub owner: isize,
+    pub is_cloaked: bool,
+    pub is_manageable: bool,
+    pub decision: String,
+    pub rules: Vec<usize>,
+}
+
+impl WindowReport {
+    unsafe fn from_window(hwnd: &HWND, processes: &HashMap<u32, ProcessEntry>) -> Result<WindowReport> {
+        let client = Client::from_window(hwnd, processes)?;
+        let decision = DwmrApp::is_manageable(hwnd)?;
+        let rules = config().rules.iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.is_match(&client))
+            .map(|(index, _)| index)
+            .collect();
+
+        Ok(WindowReport {
+            hwnd: hwnd.0,
+            title: client.title,
+            class: client.class,
+            process_name: client.process_name,
+            process_id: client.process_id,
+            style: GetWindowLongW(*hwnd, GWL_STYLE) as u32,
+            exstyle: GetWindowLongW(*hwnd, GWL_EXSTYLE) as u32,
+            owner: GetWindow(*hwnd, GW_OWNER).0,
+            is_cloaked: DwmrApp::is_cloaked(hwnd).unwrap_or(false),
+            is_manageable: decision.is_manage(),
+            decision: decision.to_string(),
+            rules,
+        })
+    }
+}
+
+pub unsafe fn inspect_windows() -> Result<Vec<WindowReport>> {
+    let mut hwnds: Vec<HWND> = Vec::new();
+    EnumWindows(Some(collect_windows), LPARAM(&mut hwnds as *mut _ as isize))?;
+
+    let processes = snapshot_processes().unwrap_or_default();
+    let mut reports = Vec::with_capacity(hwnds.len());
+    for hwnd in hwnds.iter() {
+        match WindowReport::from_window(hwnd, &processes) {
+            Ok(report) => reports.push(report),
+            Err(e) => println!("Error: failed to inspect window {} - {e}", hwnd.0),
+        }
+    }
+    Ok(reports)
+}
+
+pub unsafe extern "system" fn collect_windows(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let hwnds = lparam.0 as *mut Vec<HWND>;
+    if hwnds.is_null() {
+        return FALSE;
+    }
+
+    (*hwnds).push(hwnd);
+    TRUE
+}
+
+pub fn format_table(reports: &[WindowReport]) -> String {
+    let mut table = format!(
+        "{:<10} {:<32} {:<32} {:<24} {:>7} {:<10} {:<10} {:<10} {:<7} {:<12} {}\n",
+        "HWND", "TITLE", "CLASS", "PROCESS", "PID", "STYLE", "EXSTYLE", "OWNER", "CLOAKED", "RULES", "DECISION");
+
+    for report in reports.iter() {
+        let rules = report.rules.iter().map(|index| index.to_string()).collect::<Vec<_>>().join(",");
+        table += &format!(
+            "{:<10} {:<32} {:<32} {:<24} {:>7} {:<#10x} {:<#10x} {:<10} {:<7} {:<12} {}\n",
+            report.hwnd,
+            truncate(&report.title, 32),
+            truncate(&report.class, 32),
+            truncate(&report.process_name, 24),
+            report.process_id,
+            report.style,
+            report.exstyle,
+            report.owner,
+            report.is_cloaked,
+            if rules.is_empty() { "-".to_string() } else { rules },
+            report.decision);
+    }
+    table
+}
+
+pub fn format_json(reports: &[WindowReport]) -> String {
+    serde_json::to_string_pretty(reports).unwrap_or_default()
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+
+    let mut truncated: String = text.chars().take(width - 1).collect();
+    truncated.push('…');
+    truncated
+}
@@ -0,0 +1,224 @@
+use core::fmt;
+use std::str::FromStr;
+use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+// Display order of the modifiers, e.g. "Ctrl+Alt+Shift+Win+F5".
+const MODIFIER_NAMES: [(HOT_KEY_MODIFIERS, &str); 4] = [
+    (MOD_CONTROL, "Ctrl"),
+    (MOD_ALT, "Alt"),
+    (MOD_SHIFT, "Shift"),
+    (MOD_WIN, "Win"),
+];
+
+const MODIFIER_ALIASES: [(&str, HOT_KEY_MODIFIERS); 2] = [
+    ("Control", MOD_CONTROL),
+    ("Super", MOD_WIN),
+];
+
+// Names of the keys that aren't a letter, a digit or a function key. The first name of a key is used for display.
+const KEY_NAMES: [(&str, VIRTUAL_KEY); 66] = [
+    ("Return", VK_RETURN),
+    ("Enter", VK_RETURN),
+    ("Space", VK_SPACE),
+    ("Tab", VK_TAB),
+    ("Escape", VK_ESCAPE),
+    ("Esc", VK_ESCAPE),
+    ("Backspace", VK_BACK),
+    ("Delete", VK_DELETE),
+    ("Del", VK_DELETE),
+    ("Insert", VK_INSERT),
+    ("Ins", VK_INSERT),
+    ("Home", VK_HOME),
+    ("End", VK_END),
+    ("PageUp", VK_PRIOR),
+    ("PageDown", VK_NEXT),
+    ("Left", VK_LEFT),
+    ("Right", VK_RIGHT),
+    ("Up", VK_UP),
+    ("Down", VK_DOWN),
+    ("PrintScreen", VK_SNAPSHOT),
+    ("Pause", VK_PAUSE),
+    ("CapsLock", VK_CAPITAL),
+    ("NumLock", VK_NUMLOCK),
+    ("ScrollLock", VK_SCROLL),
+    ("Apps", VK_APPS),
+    ("Numpad0", VK_NUMPAD0),
+    ("Numpad1", VK_NUMPAD1),
+    ("Numpad2", VK_NUMPAD2),
+    ("Numpad3", VK_NUMPAD3),
+    ("Numpad4", VK_NUMPAD4),
+    ("Numpad5", VK_NUMPAD5),
+    ("Numpad6", VK_NUMPAD6),
+    ("Numpad7", VK_NUMPAD7),
+    ("Numpad8", VK_NUMPAD8),
+    ("Numpad9", VK_NUMPAD9),
+    ("Multiply", VK_MULTIPLY),
+    ("Add", VK_ADD),
+    ("Subtract", VK_SUBTRACT),
+    ("Decimal", VK_DECIMAL),
+    ("Divide", VK_DIVIDE),
+    ("Oem_Plus", VK_OEM_PLUS),
+    ("Oem_Comma", VK_OEM_COMMA),
+    ("Oem_Minus", VK_OEM_MINUS),
+    ("Oem_Period", VK_OEM_PERIOD),
+    ("Oem_1", VK_OEM_1),
+    ("Oem_2", VK_OEM_2),
+    ("Oem_3", VK_OEM_3),
+    ("Oem_4", VK_OEM_4),
+    ("Oem_5", VK_OEM_5),
+    ("Oem_6", VK_OEM_6),
+    ("Oem_7", VK_OEM_7),
+    ("Oem_8", VK_OEM_8),
+    ("Oem_102", VK_OEM_102),
+    ("VolumeMute", VK_VOLUME_MUTE),
+    ("VolumeDown", VK_VOLUME_DOWN),
+    ("VolumeUp", VK_VOLUME_UP),
+    ("MediaNext", VK_MEDIA_NEXT_TRACK),
+    ("MediaPrev", VK_MEDIA_PREV_TRACK),
+    ("MediaStop", VK_MEDIA_STOP),
+    ("MediaPlayPause", VK_MEDIA_PLAY_PAUSE),
+    ("BrowserBack", VK_BROWSER_BACK),
+    ("BrowserForward", VK_BROWSER_FORWARD),
+    ("BrowserRefresh", VK_BROWSER_REFRESH),
+    ("BrowserHome", VK_BROWSER_HOME),
+    ("LaunchMail", VK_LAUNCH_MAIL),
+    ("LaunchApp1", VK_LAUNCH_APP1),
+];
+
+// How a binding is grabbed. `Hook` reads every key press through a low-level keyboard hook, which
+// also takes shortcuts reserved by the shell or owned by another application.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyBackend {
+    // `RegisterHotKey`, falling back to the hook when the hotkey is already taken.
+    #[default]
+    Auto,
+    HotKey,
+    Hook,
+}
+
+impl FromStr for KeyBackend {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<KeyBackend, String> {
+        match name.to_lowercase().as_str() {
+            "auto" => Ok(KeyBackend::Auto),
+            "hotkey" => Ok(KeyBackend::HotKey),
+            "hook" => Ok(KeyBackend::Hook),
+            _ => Err(format!("unknown key backend {name:?}, expected \"auto\", \"hotkey\" or \"hook\"")),
+        }
+    }
+}
+
+// A hotkey written as "Alt+Shift+Return", "Win+F5" or "Ctrl+Alt+Left". Names are case-insensitive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeySpec {
+    pub modifiers: HOT_KEY_MODIFIERS,
+    pub key: VIRTUAL_KEY,
+}
+
+impl KeySpec {
+    pub fn new(modifiers: HOT_KEY_MODIFIERS, key: VIRTUAL_KEY) -> KeySpec {
+        KeySpec { modifiers, key }
+    }
+
+    // "Mod" stands for `mod_key`, the modifier configured for the default bindings.
+    pub fn parse(spec: &str, mod_key: HOT_KEY_MODIFIERS) -> Result<KeySpec, String> {
+        let parts: Vec<&str> = spec.split('+').map(|part| part.trim()).collect();
+        let (key_name, modifier_names) = parts.split_last().unwrap();
+        if key_name.is_empty() {
+            return Err(format!("missing key in {spec:?}"));
+        }
+
+        let mut modifiers = HOT_KEY_MODIFIERS(0);
+        for modifier_name in modifier_names.iter() {
+            modifiers |= parse_modifier(modifier_name, mod_key)
+                .ok_or_else(|| format!("unknown modifier {modifier_name:?} in {spec:?}"))?;
+        }
+
+        let key = parse_key_name(key_name).ok_or_else(|| format!("unknown key {key_name:?} in {spec:?}"))?;
+        Ok(KeySpec { modifiers, key })
+    }
+}
+
+impl FromStr for KeySpec {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<KeySpec, String> {
+        KeySpec::parse(spec, HOT_KEY_MODIFIERS(0))
+    }
+}
+
+// The modifiers held down right now, for the keyboard hook.
+pub unsafe fn pressed_modifiers() -> HOT_KEY_MODIFIERS {
+    let is_down = |key: VIRTUAL_KEY| (GetAsyncKeyState(key.0 as i32) as u16 & 0x8000) != 0;
+    let mut modifiers = HOT_KEY_MODIFIERS(0);
+    if is_down(VK_CONTROL) {
+        modifiers |= MOD_CONTROL;
+    }
+    if is_down(VK_MENU) {
+        modifiers |= MOD_ALT;
+    }
+    if is_down(VK_SHIFT) {
+        modifiers |= MOD_SHIFT;
+    }
+    if is_down(VK_LWIN) || is_down(VK_RWIN) {
+        modifiers |= MOD_WIN;
+    }
+    modifiers
+}
+
+pub fn parse_modifier(name: &str, mod_key: HOT_KEY_MODIFIERS) -> Option<HOT_KEY_MODIFIERS> {
+    if name.eq_ignore_ascii_case("Mod") {
+        return Some(mod_key);
+    }
+
+    MODIFIER_NAMES.iter()
+        .map(|(modifier, name)| (*name, *modifier))
+        .chain(MODIFIER_ALIASES)
+        .find(|(modifier_name, _)| modifier_name.eq_ignore_ascii_case(name))
+        .map(|(_, modifier)| modifier)
+}
+
+pub fn parse_key_name(name: &str) -> Option<VIRTUAL_KEY> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_alphanumeric() {
+            return Some(VIRTUAL_KEY(c.to_ascii_uppercase() as u16));
+        }
+    }
+
+    if let Some(code) = name.strip_prefix("0x").and_then(|code| u16::from_str_radix(code, 16).ok()) {
+        return Some(VIRTUAL_KEY(code));
+    }
+
+    if let Some(number) = name.strip_prefix(['F', 'f']).and_then(|number| number.parse::<u16>().ok()) {
+        if (1..=24).contains(&number) {
+            return Some(VIRTUAL_KEY(VK_F1.0 + number - 1));
+        }
+    }
+
+    KEY_NAMES.iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, key)| *key)
+}
+
+pub fn key_name(key: VIRTUAL_KEY) -> String {
+    match key.0 {
+        0x30..=0x39 | 0x41..=0x5A => (key.0 as u8 as char).to_string(),
+        code if (VK_F1.0..=VK_F24.0).contains(&code) => format!("F{}", code - VK_F1.0 + 1),
+        code => KEY_NAMES.iter()
+            .find(|(_, named_key)| *named_key == key)
+            .map_or_else(|| format!("0x{code:02X}"), |(name, _)| name.to_string()),
+    }
+}
+
+impl fmt::Display for KeySpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in MODIFIER_NAMES.iter() {
+            if (self.modifiers & *modifier) == *modifier {
+                write!(f, "{name}+")?;
+            }
+        }
+        write!(f, "{}", key_name(self.key))
+    }
+}
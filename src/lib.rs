@@ -4,9 +4,11 @@ use windows::{
     Win32::{
         UI::HiDpi::*,
         System::{
+            DataExchange::*,
             Diagnostics::Debug::*, 
+            Memory::*,
+            Ole::CF_UNICODETEXT,
             Threading::*, 
-            ProcessStatus::*,
         },
         Foundation::*,
         UI::{
@@ -28,15 +30,27 @@ use core::fmt;
 use std::{
     collections::*,
     mem::size_of,
+    time::SystemTime,
     usize,
     cmp::*,
 };
 
 pub mod config;
+pub mod config_file;
 pub mod graphic_utils;
+pub mod matcher;
+pub mod process_utils;
+pub mod inspector;
+pub mod key_spec;
+pub mod spawn;
 
 use config::*;
+use config_file::*;
 use graphic_utils::*;
+use matcher::*;
+use process_utils::*;
+use key_spec::*;
+use spawn::*;
 
 #[cfg(test)]
 mod test;
@@ -55,8 +69,38 @@ const W_BAR_NAME: PCWSTR = w!("dwmr-bar");
 
 const W_WALLPAPER_CLASS_NAME: PCWSTR = w!("Progman");
 const BAR_HEIGHT: i32 = 20;
-const TAGMASK: u32 = (1 << TAGS.len()) - 1;
 const WM_UPDATE_DISPLAY: u32 = WM_USER + 1;
+const WM_RELOAD_CONFIG: u32 = WM_USER + 2;
+const CONFIG_WATCH_TIMER_ID: usize = 1;
+const CONFIG_WATCH_INTERVAL_MS: u32 = 1000;
+const MODE_TIMER_ID: usize = 2;
+// Hotkey ids of the active mode's keys start here, after the ids of the regular bindings.
+const MODE_HOTKEY_ID_BASE: usize = 0x8000;
+const MODE_EXIT_HOTKEY_ID: usize = MODE_HOTKEY_ID_BASE - 1;
+// An unassigned key pressed after a hooked shortcut, so releasing Win or Alt alone doesn't open the Start menu or a menu bar.
+const MASK_KEY: VIRTUAL_KEY = VIRTUAL_KEY(0xE8);
+const HSHELL_FLASH: u32 = HSHELL_REDRAW | HSHELL_HIGHBIT;
+
+fn tagmask() -> u32 {
+    config().tagmask()
+}
+
+// Drops tags that no longer exist; a window or monitor left without any falls back to `fallback`.
+pub fn mask_tags(tags: u32, tagmask: u32, fallback: u32) -> u32 {
+    match tags & tagmask {
+        0 => fallback & tagmask,
+        tags => tags,
+    }
+}
+
+// Asks a running instance to reload its configuration. Returns whether the new configuration was applied.
+pub unsafe fn send_reload() -> Result<bool> {
+    let hwnd = FindWindowExW(HWND_MESSAGE, None, W_APP_NAME, None);
+    if hwnd.0 == 0 {
+        return Err(Error::new(E_FAIL, HSTRING::from("dwmr-win32 is not running")));
+    }
+    Ok(SendMessageW(hwnd, WM_RELOAD_CONFIG, WPARAM::default(), LPARAM::default()).0 != 0)
+}
 
 #[derive(Default, Clone, Debug)]
 pub struct Rect {
@@ -75,6 +119,15 @@ impl Rect {
             height: rect.bottom - rect.top
         }
     }
+
+    fn centered_in(&self, area: &Rect) -> Rect {
+        Rect {
+            x: area.x + (area.width - self.width) / 2,
+            y: area.y + (area.height - self.height) / 2,
+            width: self.width,
+            height: self.height
+        }
+    }
 }
 
 impl PartialEq for Rect {
@@ -92,16 +145,36 @@ pub struct Bar {
     rect: Rect,
     is_selected_monitor: bool,
     render_target: Option<ID2D1HwndRenderTarget>,
-    unselected_text_brush: Option<ID2D1SolidColorBrush>,
-    selected_text_brush: Option<ID2D1SolidColorBrush>,
-    text_box_brush: Option<ID2D1SolidColorBrush>,
-    background_brush: Option<ID2D1SolidColorBrush>,
+    theme: Theme,
+    brushes: Option<ThemeBrushes>,
     text_format: Option<IDWriteTextFormat>,
     write_factory: Option<IDWriteFactory>,
     dpi: f32,
     selected_tags: u32,
     window_tags: u32,
     current_window_tags: u32,
+    urgent_tags: u32,
+    mode: Option<String>,
+    is_passthrough: bool,
+    layout_symbol: String,
+}
+
+#[derive(Debug, Clone)]
+struct SchemeBrushes {
+    text: ID2D1SolidColorBrush,
+    background: ID2D1SolidColorBrush,
+}
+
+// A brush pair for every scheme of the bar's theme.
+#[derive(Debug, Clone)]
+struct ThemeBrushes {
+    normal: SchemeBrushes,
+    selected: SchemeBrushes,
+    occupied: SchemeBrushes,
+    urgent: SchemeBrushes,
+    inactive_monitor: SchemeBrushes,
+    layout_symbol: SchemeBrushes,
+    status: SchemeBrushes,
 }
 
 impl Drop for Bar {
@@ -137,8 +210,6 @@ impl Bar {
             GetLastError()?;
         }
 
-        SetLayeredWindowAttributes(hwnd_result, COLORREF(0), (255 as f32 * BAR_TRANSPARENCY) as u8, LWA_ALPHA)?;
-
         self.hwnd = hwnd_result;
         self.rect = Rect {
             x: 0,
@@ -170,38 +241,55 @@ impl Bar {
             presentOptions: D2D1_PRESENT_OPTIONS_NONE,
         };
         let render_target = factory.CreateHwndRenderTarget(&render_target_property, &hwnd_render_target_properties)?;
+        self.render_target = Some(render_target);
+        self.write_factory = Some(DWriteCreateFactory::<IDWriteFactory>(DWRITE_FACTORY_TYPE_ISOLATED)?);
+        self.apply_style()?;
+
+        ShowWindow(hwnd_result, SW_SHOW);
+        UpdateWindow(hwnd_result);
+        SetForegroundWindow(focus_hwnd);
+        Ok(())
+    }
+
+    // Transparency and font come from the configuration and the brushes from `theme`; they are
+    // rebuilt when either changes.
+    unsafe fn apply_style(&mut self) -> Result<()> {
+        let config = config();
+        SetLayeredWindowAttributes(self.hwnd, COLORREF(0), (255 as f32 * config.bar_transparency) as u8, LWA_ALPHA)?;
 
+        let render_target = self.render_target.as_ref().unwrap();
         let brush_property = D2D1_BRUSH_PROPERTIES { 
             opacity: 1.0, 
             transform: Matrix3x2::identity()
         };
 
-        let background_brush = render_target.CreateSolidColorBrush(&BAR_COLOR_BACKGROUND, Some(&brush_property as *const _))?;
-        let selected_box_brush = render_target.CreateSolidColorBrush(&BAR_COLOR_SELECTED_BOX, Some(&brush_property as *const _))?;
-        let selected_text_brush = render_target.CreateSolidColorBrush(&BAR_COLOR_SELECTED_TEXT, Some(&brush_property as *const _))?;
-        let unselected_text_brush = render_target.CreateSolidColorBrush(&BAR_COLOR_UNSELECTED_TEXT, Some(&brush_property as *const _))?;
-        self.render_target = Some(render_target);
-        self.unselected_text_brush = Some(unselected_text_brush.clone());
-        self.selected_text_brush = Some(selected_text_brush);
-        self.text_box_brush = Some(selected_box_brush);
-        self.background_brush = Some(background_brush);
-
-        let write_factory = DWriteCreateFactory::<IDWriteFactory>(DWRITE_FACTORY_TYPE_ISOLATED)?;
-        let text_format = write_factory.CreateTextFormat(
-            w!("Arial"), 
+        let create_brushes = |scheme: &ColorScheme| -> Result<SchemeBrushes> {
+            Ok(SchemeBrushes {
+                text: render_target.CreateSolidColorBrush(&scheme.text, Some(&brush_property as *const _))?,
+                background: render_target.CreateSolidColorBrush(&scheme.background, Some(&brush_property as *const _))?,
+            })
+        };
+        let theme = &self.theme;
+        let brushes = ThemeBrushes {
+            normal: create_brushes(&theme.normal)?,
+            selected: create_brushes(&theme.selected)?,
+            occupied: create_brushes(&theme.occupied)?,
+            urgent: create_brushes(&theme.urgent)?,
+            inactive_monitor: create_brushes(&theme.inactive_monitor)?,
+            layout_symbol: create_brushes(&theme.layout_symbol)?,
+            status: create_brushes(&theme.status)?,
+        };
+        self.brushes = Some(brushes);
+
+        let text_format = self.write_factory.as_ref().unwrap().CreateTextFormat(
+            &HSTRING::from(&config.bar_font), 
             None, 
             DWRITE_FONT_WEIGHT_REGULAR, 
             DWRITE_FONT_STYLE_NORMAL, 
             DWRITE_FONT_STRETCH_NORMAL,
             20.0, 
             w!("ko-kr"))?;
-
-        self.write_factory = Some(write_factory);
         self.text_format = Some(text_format);
-
-        ShowWindow(hwnd_result, SW_SHOW);
-        UpdateWindow(hwnd_result);
-        SetForegroundWindow(focus_hwnd);
         Ok(())
     }
 
@@ -255,87 +343,67 @@ impl Bar {
             return Ok(());
         }
 
-        if self.render_target.is_none() || self.text_box_brush.is_none() || self.text_format.is_none(){
+        if self.render_target.is_none() || self.brushes.is_none() || self.text_format.is_none(){
             return Ok(());
         }
 
         let render_target_ref = self.render_target.as_ref().unwrap();
         render_target_ref.BeginDraw();
 
-        render_target_ref.Clear(Some(&BAR_COLOR_BACKGROUND));
+        let config = config();
+        let brushes = self.brushes.as_ref().unwrap();
+        render_target_ref.Clear(Some(&self.theme.normal.background));
 
+        let selected_window_mark = HSTRING::from(&config.bar_selected_window_mark);
+        let unselected_window_mark = HSTRING::from(&config.bar_unselected_window_mark);
+        let font_size = config.bar_font_size;
         let mut x_pos = 0.0;
-        for i in 0..TAGS.len() {
-            let window_exist = has_flag!(self.window_tags, TAGMASK & (1 << i));
+        for (i, tag) in config.tags.iter().enumerate() {
+            let window_exist = has_flag!(self.window_tags, config.tagmask() & (1 << i));
             let is_selected_tag = has_flag!(self.selected_tags, 1 << i);
             let window_mark = match (window_exist, is_selected_tag) {
                 (false, _) => None,
-                (true, true) => Some(BAR_SELECTED_WINDOW_MARK.as_wide()),
-                (true, false) => Some(BAR_UNSELECTED_WINDOW_MARK.as_wide()),
+                (true, true) => Some(selected_window_mark.as_wide()),
+                (true, false) => Some(unselected_window_mark.as_wide()),
             };
 
-            let marked_tag = HSTRING::from(TAGS[i].to_string().unwrap() + CURRENT_WINDOW_MARK);
             let display_tag = if has_flag!(self.current_window_tags, 1 << i) {
-                marked_tag.as_wide()
+                HSTRING::from(tag.clone() + &config.current_window_mark)
             } else {
-                TAGS[i].as_wide()
+                HSTRING::from(tag)
             };
-
-            x_pos = match (has_flag!(self.selected_tags, 1 << i), self.is_selected_monitor) {
-                (true, true ) => self.draw_selected_monitor_selected_text_box(display_tag, window_mark, 15.0, x_pos)?,
-                (true, false) => self.draw_unselected_monitor_selected_text_box(display_tag, window_mark, 15.0, x_pos)?,
-                (false, _   ) => self.draw_unselected_text_box(display_tag, window_mark, 15.0, x_pos)?
-
+            let display_tag = display_tag.as_wide();
+
+            let scheme = match (has_flag!(self.urgent_tags, 1 << i), has_flag!(self.selected_tags, 1 << i), self.is_selected_monitor, window_exist) {
+                (true, _, _, _          ) => &brushes.urgent,
+                (false, true, true, _   ) => &brushes.selected,
+                (false, true, false, _  ) => &brushes.inactive_monitor,
+                (false, false, _, true  ) => &brushes.occupied,
+                (false, false, _, false ) => &brushes.normal,
             };
+            x_pos = self.draw_text_box(scheme, display_tag, window_mark, font_size, x_pos)?;
             x_pos += 5.0;
         }
 
-        render_target_ref.EndDraw(None, None)?;
+        let layout_symbol = HSTRING::from(&self.layout_symbol);
+        x_pos = self.draw_text_box(&brushes.layout_symbol, layout_symbol.as_wide(), None, font_size, x_pos)?;
 
-        Ok(())
-    }
+        if let Some(mode) = &self.mode {
+            let mode = HSTRING::from(mode);
+            x_pos = self.draw_text_box(&brushes.status, mode.as_wide(), None, font_size, x_pos + 5.0)?;
+        }
 
-    unsafe fn draw_unselected_text_box(&self, text: &[u16], super_text: Option<&[u16]>, font_size: f32, origin_x: f32) -> Result<f32> 
-    {
-        let next_width = implement_draw_text_box(
-            text, 
-            super_text,
-            font_size, 
-            self.rect.width as f32, 
-            self.rect.height as f32, 
-            origin_x, 
-            self.rect.y as f32,
-            BAR_PADDING, 
-            self.dpi, 
-            self.text_format.as_ref().unwrap(), 
-            self.write_factory.as_ref().unwrap(), 
-            self.render_target.as_ref().unwrap(), 
-            self.background_brush.as_ref().unwrap(),
-            self.unselected_text_brush.as_ref().unwrap())?;
-        Ok(next_width)
-    }
+        if self.is_passthrough {
+            let passthrough_mark = HSTRING::from(&config.bar_passthrough_mark);
+            self.draw_text_box(&brushes.status, passthrough_mark.as_wide(), None, font_size, x_pos + 5.0)?;
+        }
 
-    unsafe fn draw_selected_monitor_selected_text_box(&self, text: &[u16], super_text: Option<&[u16]>, font_size: f32, origin_x: f32) -> Result<f32> 
-    {
-        let next_width = implement_draw_text_box(
-            text, 
-            super_text,
-            font_size, 
-            self.rect.width as f32, 
-            self.rect.height as f32, 
-            origin_x, 
-            self.rect.y as f32,
-            BAR_PADDING, 
-            self.dpi, 
-            self.text_format.as_ref().unwrap(), 
-            self.write_factory.as_ref().unwrap(), 
-            self.render_target.as_ref().unwrap(), 
-            self.text_box_brush.as_ref().unwrap(),
-            self.selected_text_brush.as_ref().unwrap())?;
-        Ok(next_width)
+        render_target_ref.EndDraw(None, None)?;
+
+        Ok(())
     }
 
-    unsafe fn draw_unselected_monitor_selected_text_box(&self, text: &[u16], super_text: Option<&[u16]>, font_size: f32, origin_x: f32) -> Result<f32> 
+    unsafe fn draw_text_box(&self, scheme: &SchemeBrushes, text: &[u16], super_text: Option<&[u16]>, font_size: f32, origin_x: f32) -> Result<f32> 
     {
         let next_width = implement_draw_text_box(
             text, 
@@ -345,13 +413,13 @@ impl Bar {
             self.rect.height as f32, 
             origin_x, 
             self.rect.y as f32,
-            BAR_PADDING, 
+            config().bar_padding, 
             self.dpi, 
             self.text_format.as_ref().unwrap(), 
             self.write_factory.as_ref().unwrap(), 
             self.render_target.as_ref().unwrap(), 
-            self.unselected_text_brush.as_ref().unwrap(),
-            self.selected_text_brush.as_ref().unwrap())?;
+            &scheme.background,
+            &scheme.text)?;
         Ok(next_width)
     }
 }
@@ -371,6 +439,7 @@ struct Monitor {
     selected_tag_index: usize,
     bar: Bar,
     layout: Layout,
+    attach: Attach,
 }
 
 impl Monitor {
@@ -412,6 +481,22 @@ impl Monitor {
         Ok(())
     }
 
+    // Clients are kept in reversed order, so the master is the last element and the bottom of the stack is the first.
+    fn attach(&mut self, client: Client, attach: Attach) {
+        let clients_count = self.clients.len();
+        let master_count = min(self.master_count as usize, clients_count);
+        let selected_client_index = self.get_selected_client_index();
+        let index = match (attach, selected_client_index) {
+            (Attach::Master, _) => clients_count,
+            (Attach::Aside, _) => clients_count - master_count,
+            (Attach::Below, Some(selected_client_index)) => selected_client_index,
+            (Attach::Above, Some(selected_client_index)) => selected_client_index + 1,
+            (Attach::Below | Attach::Above, None) => clients_count,
+            (Attach::Bottom, _) => 0,
+        };
+        self.clients.insert(index, client);
+    }
+
     pub fn find_client_index(&self, hwnd: &HWND) -> Option<usize> {
         if hwnd.0 == 0 {
             return None;
@@ -429,7 +514,7 @@ impl Monitor {
     }
 
     pub fn is_visible(client: &Client, visible_tags: u32) -> bool {
-        return (visible_tags & client.tags) != 0
+        return client.is_sticky || (visible_tags & client.tags) != 0
     }
 
     pub fn visible_clinets_count(&self) -> i32 {
@@ -450,6 +535,11 @@ impl Monitor {
         self.clients.retain(|client| IsWindow(client.hwnd) == TRUE);
     }
 
+    pub fn name_string(&self) -> String {
+        let name_length = self.name.iter().position(|c| *c == 0).unwrap_or(self.name.len());
+        String::from_utf16_lossy(&self.name[..name_length])
+    }
+
     pub fn is_in_monitor(&self, x: i32, y: i32) -> bool {
         let left_check = self.rect.x <= x;
         let right_check = x <= self.rect.x + self.rect.width;
@@ -461,9 +551,12 @@ impl Monitor {
 
     pub unsafe fn update_bar(&mut self, is_selected_monitor: bool) {
         let window_tags = self.clients.iter().fold(0u32, |acc, client| -> u32 { acc | client.tags });
+        let urgent_tags = self.clients.iter().filter(|client| client.is_urgent).fold(0u32, |acc, client| -> u32 { acc | client.tags });
         self.bar.window_tags = window_tags;
+        self.bar.urgent_tags = urgent_tags;
         self.bar.selected_tags = self.tagset[self.selected_tag_index];
         self.bar.is_selected_monitor = is_selected_monitor;
+        self.bar.layout_symbol = self.layout.symbol().to_string();
         self.bar.current_window_tags = 0;
         if let Some(client_index) = self.get_selected_client_index() {
             self.bar.current_window_tags = self.clients[client_index].tags;
@@ -520,6 +613,15 @@ impl Layout {
     }
 }
 
+impl Layout {
+    fn symbol(&self) -> &'static str {
+        match self {
+            Layout::Tile(_) => "[]=",
+            Layout::Stack(_) => "===",
+        }
+    }
+}
+
 impl Default for Layout {
     fn default() -> Self {
         Layout::Tile(TileLayout::default())
@@ -680,6 +782,17 @@ impl LayoutTrait for StackLayout {
     }
 }
 
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attach {
+    #[default]
+    Master,
+    Aside,
+    Below,
+    Above,
+    Bottom,
+}
+
+#[derive(Clone, Copy)]
 pub union Arg {
     i: i32,
     ui: u32,
@@ -687,11 +800,22 @@ pub union Arg {
     l: Layout
 }
 
+#[derive(Clone)]
 pub struct Key {
     pub mod_key: HOT_KEY_MODIFIERS,
-    pub key: char,
+    pub key: VIRTUAL_KEY,
     pub func: unsafe fn(&mut DwmrApp, &Option<Arg>)->Result<()>,
-    pub arg: Option<Arg>
+    pub arg: Option<Arg>,
+    // `None` uses `key_backend` from the configuration.
+    pub backend: Option<KeyBackend>,
+    // Name of the action in `ACTIONS`.
+    pub action: &'static str,
+}
+
+impl Key {
+    pub fn spec(&self) -> KeySpec {
+        KeySpec::new(self.mod_key, self.key)
+    }
 }
 
 
@@ -700,9 +824,13 @@ pub struct Client {
     hwnd: HWND,
     title: String,
     class: String,
-    process_filename: String,
+    process_name: String,
+    process_path: String,
+    process_id: u32,
+    parent_process_name: String,
     parent: HWND,
     root: HWND,
+    owner: HWND,
     rect: Rect,
     bw: i32,
     tags: u32,
@@ -715,38 +843,389 @@ pub struct Client {
     is_urgent: bool,
     is_cloaked: bool,
     is_hide: bool,
+    is_sticky: bool,
+    is_no_focus: bool,
     monitor: usize,
+    swallowed: Option<Box<Client>>,
+}
+
+impl Client {
+    // Identity of a window as seen by rules; placement and state are filled in by `DwmrApp::manage`.
+    // `processes` is a snapshot shared by every window looked up at the same time.
+    pub unsafe fn from_window(hwnd: &HWND, processes: &HashMap<u32, ProcessEntry>) -> Result<Client> {
+        let title = DwmrApp::get_window_title(hwnd);
+
+        let mut class_name_buf = [0u16; 256];
+        SetLastError(WIN32_ERROR(0));
+        if GetClassNameW(*hwnd, class_name_buf.as_mut()) == 0 {
+            if let Err(e) = GetLastError() {
+                println!("Error: failed to get class name - {e}");
+            }
+        }
+        let class = PCWSTR::from_raw(class_name_buf.as_ptr()).to_string().unwrap_or_default();
+
+        let process_id = get_process_id(hwnd).unwrap_or_default();
+        let process_path = get_process_path(process_id).unwrap_or_default();
+        let process_name = file_basename(&process_path).to_string();
+        let parent_process_name = get_parent_process(processes, process_id)
+            .map(|parent| parent.exe_name.clone())
+            .unwrap_or_default();
+
+        Ok(Client {
+            hwnd: *hwnd,
+            title,
+            class,
+            process_name,
+            process_path,
+            process_id,
+            parent_process_name,
+            parent: GetParent(*hwnd),
+            root: DwmrApp::get_root(hwnd)?,
+            ..Default::default()
+        })
+    }
+
+    // With `float_owned_windows`, dialogs and windows owned by a managed client float, and owned
+    // windows keep their owner's tags and monitor.
+    fn inherit_owner(&mut self, owner: Option<&Client>, config: &Config) {
+        if !config.float_owned_windows {
+            return;
+        }
+
+        if config.dialog_classes.contains(&self.class) {
+            self.is_floating = true;
+        }
+
+        if let Some(owner) = owner {
+            self.owner = owner.hwnd;
+            self.tags = owner.tags;
+            self.monitor = owner.monitor;
+            self.is_floating = true;
+        }
+    }
 }
 
 impl fmt::Display for Client {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "HWND: {} | Title: {} | Class: {} | Process Filename: {}", self.hwnd.0, self.title, self.class, self.process_filename)
+        write!(f, "HWND: {} | Title: {} | Class: {} | Process: {} ({}) | Path: {} | Parent Process: {}", self.hwnd.0, self.title, self.class, self.process_name, self.process_id, self.process_path, self.parent_process_name)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum MonitorTarget {
+    Index(usize),
+    Name(String),
+}
+
+// Both variants are relative to the work area of the client's monitor.
+#[derive(Debug, Clone, Copy)]
+pub enum RuleRect {
+    Absolute{x: i32, y: i32, width: i32, height: i32},
+    Percent{x: f32, y: f32, width: f32, height: f32},
+}
+
+impl RuleRect {
+    fn resolve(&self, area: &Rect) -> Rect {
+        match *self {
+            RuleRect::Absolute{x, y, width, height} => Rect {
+                x: area.x + x,
+                y: area.y + y,
+                width,
+                height
+            },
+            RuleRect::Percent{x, y, width, height} => Rect {
+                x: area.x + (area.width as f32 * x) as i32,
+                y: area.y + (area.height as f32 * y) as i32,
+                width: (area.width as f32 * width) as i32,
+                height: (area.height as f32 * height) as i32
+            },
+        }
     }
 }
 
+#[derive(Default, Clone, Debug)]
 pub struct Rule {
-    title: Option<String>,
-    class: Option<String>,
-    process_filename: Option<String>,
-    is_floating: bool,
-    tags: u32
+    title: Option<Matcher>,
+    class: Option<Matcher>,
+    process_name: Option<Matcher>,
+    process_path: Option<Matcher>,
+    parent_process_name: Option<Matcher>,
+    is_floating: Option<bool>,
+    tags: Option<u32>,
+    attach: Option<Attach>,
+    monitor: Option<MonitorTarget>,
+    rect: Option<RuleRect>,
+    is_centered: Option<bool>,
+    is_sticky: Option<bool>,
+    no_focus: Option<bool>,
+    switch_to_tag: Option<bool>,
+    no_swallow: Option<bool>,
+    reapply_on_title_change: bool,
+    priority: i32,
+    stop: bool,
 }
 
 impl Rule {
     pub fn is_match(&self, client: &Client) -> bool {
-        if self.title.is_some() && !client.title.contains(self.title.as_ref().unwrap()) {
+        if self.title.is_some() && !self.title.as_ref().unwrap().is_match(&client.title) {
+            return false;
+        }
+
+        if self.class.is_some() && !self.class.as_ref().unwrap().is_match(&client.class) {
+            return false;
+        }
+
+        if self.process_name.is_some() && !self.process_name.as_ref().unwrap().is_match(&client.process_name) {
             return false;
         }
 
-        if self.class.is_some() && self.class.as_ref().unwrap() != &client.class {
+        if self.process_path.is_some() && !self.process_path.as_ref().unwrap().is_match(&client.process_path) {
             return false;
         }
 
-        if self.process_filename.is_some() && !client.process_filename.contains(self.process_filename.as_ref().unwrap()) {
+        if self.parent_process_name.is_some() && !self.parent_process_name.as_ref().unwrap().is_match(&client.parent_process_name) {
             return false;
         }
         true
     }
+
+    // Whether every window matching `other` also matches this rule, judging from the matchers alone.
+    pub fn covers(&self, other: &Rule) -> bool {
+        let matchers = [
+            (&self.title, &other.title),
+            (&self.class, &other.class),
+            (&self.process_name, &other.process_name),
+            (&self.process_path, &other.process_path),
+            (&self.parent_process_name, &other.parent_process_name),
+        ];
+        matchers.iter().all(|(matcher, other_matcher)| match (matcher, other_matcher) {
+            (None, _) => true,
+            (Some(matcher), Some(other_matcher)) => matcher.to_config_string() == other_matcher.to_config_string(),
+            (Some(_), None) => false,
+        })
+    }
+
+    // Whether this rule, declared at `index`, stops the evaluation before the rule declared at `other_index`.
+    pub fn skips(&self, index: usize, other: &Rule, other_index: usize) -> bool {
+        self.stop && (self.priority > other.priority || (self.priority == other.priority && index < other_index))
+    }
+
+    // Matching rules are applied in ascending priority, then declaration order, and later rules override
+    // only the fields they set. A matching rule with `stop` skips the rules of a lower priority and the
+    // rules of the same priority declared after it.
+    pub fn resolve<'a>(rules: impl Iterator<Item = &'a Rule>, client: &Client) -> Option<Rule> {
+        let mut matched_rules: Vec<(usize, &Rule)> = rules.enumerate().filter(|(_, rule)| rule.is_match(client)).collect();
+        let stop_rules: Vec<(usize, &Rule)> = matched_rules.iter().filter(|(_, rule)| rule.stop).cloned().collect();
+        matched_rules.retain(|(index, rule)| !stop_rules.iter().any(|(stop_index, stop_rule)| stop_rule.skips(*stop_index, rule, *index)));
+        matched_rules.sort_by_key(|(_, rule)| rule.priority);
+
+        let mut resolved_rule: Option<Rule> = None;
+        for (_, rule) in matched_rules {
+            resolved_rule.get_or_insert_with(Rule::default).merge(rule);
+        }
+        resolved_rule
+    }
+
+    fn merge(&mut self, other: &Rule) {
+        if other.is_floating.is_some() {
+            self.is_floating = other.is_floating;
+        }
+        if other.tags.is_some() {
+            self.tags = other.tags;
+        }
+        if other.attach.is_some() {
+            self.attach = other.attach;
+        }
+        if other.monitor.is_some() {
+            self.monitor = other.monitor.clone();
+        }
+        if other.rect.is_some() {
+            self.rect = other.rect;
+        }
+        if other.is_centered.is_some() {
+            self.is_centered = other.is_centered;
+        }
+        if other.is_sticky.is_some() {
+            self.is_sticky = other.is_sticky;
+        }
+        if other.no_focus.is_some() {
+            self.no_focus = other.no_focus;
+        }
+        if other.switch_to_tag.is_some() {
+            self.switch_to_tag = other.switch_to_tag;
+        }
+        if other.no_swallow.is_some() {
+            self.no_swallow = other.no_swallow;
+        }
+    }
+
+    pub fn from_client(client: &Client) -> Rule {
+        Rule {
+            title: (!client.title.is_empty()).then(|| Matcher::exact(&client.title)),
+            class: (!client.class.is_empty()).then(|| Matcher::exact(&client.class)),
+            process_name: (!client.process_name.is_empty()).then(|| Matcher::exact(&client.process_name).ignore_case()),
+            is_floating: Some(client.is_floating),
+            tags: Some(client.tags),
+            monitor: Some(MonitorTarget::Index(client.monitor)),
+            ..Default::default()
+        }
+    }
+
+    // Formatted as a `[[rules]]` entry of the configuration file.
+    pub fn to_config_string(&self) -> String {
+        let matchers = [
+            ("title", &self.title),
+            ("class", &self.class),
+            ("process_name", &self.process_name),
+            ("process_path", &self.process_path),
+            ("parent_process_name", &self.parent_process_name),
+        ];
+
+        let mut fields: Vec<String> = matchers.iter()
+            .filter_map(|(name, matcher)| matcher.as_ref().map(|matcher| format!("{name} = {}", matcher.to_config_string())))
+            .collect();
+
+        if let Some(is_floating) = self.is_floating {
+            fields.push(format!("is_floating = {is_floating}"));
+        }
+        if let Some(tags) = self.tags {
+            let tags: Vec<String> = (0..32).filter(|tag| tags & (1 << tag) != 0).map(|tag| (tag + 1).to_string()).collect();
+            fields.push(format!("tags = [{}]", tags.join(", ")));
+        }
+        if let Some(attach) = self.attach {
+            fields.push(format!("attach = \"{}\"", format!("{attach:?}").to_lowercase()));
+        }
+        match &self.monitor {
+            Some(MonitorTarget::Index(index)) => fields.push(format!("monitor = {index}")),
+            Some(MonitorTarget::Name(name)) => fields.push(format!("monitor = {}", toml::Value::from(name.as_str()))),
+            None => (),
+        }
+        match self.rect {
+            Some(RuleRect::Absolute{x, y, width, height}) => fields.push(format!("rect = {{ x = {x}, y = {y}, width = {width}, height = {height} }}")),
+            Some(RuleRect::Percent{x, y, width, height}) => fields.push(format!("rect = {{ x = {x}, y = {y}, width = {width}, height = {height}, percent = true }}")),
+            None => (),
+        }
+
+        let flags = [
+            ("is_centered", self.is_centered),
+            ("is_sticky", self.is_sticky),
+            ("no_focus", self.no_focus),
+            ("switch_to_tag", self.switch_to_tag),
+            ("no_swallow", self.no_swallow),
+        ];
+        for (name, flag) in flags.iter() {
+            if let Some(flag) = flag {
+                fields.push(format!("{name} = {flag}"));
+            }
+        }
+
+        if self.reapply_on_title_change {
+            fields.push("reapply_on_title_change = true".to_string());
+        }
+        if self.priority != 0 {
+            fields.push(format!("priority = {}", self.priority));
+        }
+        if self.stop {
+            fields.push("stop = true".to_string());
+        }
+
+        format!("[[rules]]\n{}\n", fields.join("\n"))
+    }
+
+    fn apply(&self, client: &mut Client) {
+        if let Some(is_floating) = self.is_floating {
+            client.is_floating = is_floating;
+        }
+        if let Some(tags) = self.tags {
+            client.tags = tags;
+        }
+        if let Some(is_sticky) = self.is_sticky {
+            client.is_sticky = is_sticky;
+        }
+        if let Some(no_focus) = self.no_focus {
+            client.is_no_focus = no_focus;
+        }
+    }
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct WindowProperties {
+    title: String,
+    class: String,
+    process_name: String,
+    style: u32,
+    exstyle: u32,
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct ExcludeRule {
+    name: String,
+    title: Option<Matcher>,
+    class: Option<Matcher>,
+    process_name: Option<Matcher>,
+    style: u32,
+    exstyle: u32,
+}
+
+impl ExcludeRule {
+    pub fn is_match(&self, window: &WindowProperties) -> bool {
+        if self.title.is_some() && !self.title.as_ref().unwrap().is_match(&window.title) {
+            return false;
+        }
+
+        if self.class.is_some() && !self.class.as_ref().unwrap().is_match(&window.class) {
+            return false;
+        }
+
+        if self.process_name.is_some() && !self.process_name.as_ref().unwrap().is_match(&window.process_name) {
+            return false;
+        }
+
+        has_flag!(window.style, self.style) && has_flag!(window.exstyle, self.exstyle)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ManageDecision {
+    Manage,
+    NotWindow,
+    Disabled,
+    NoActivate,
+    Untitled,
+    Cloaked,
+    NoClass,
+    Excluded(String),
+    Debugged,
+    ToolWindow,
+    Invisible,
+    UnmanageableParent(Box<ManageDecision>),
+    ChildWindow,
+}
+
+impl ManageDecision {
+    pub fn is_manage(&self) -> bool {
+        *self == ManageDecision::Manage
+    }
+}
+
+impl fmt::Display for ManageDecision {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ManageDecision::Manage => write!(f, "managed"),
+            ManageDecision::NotWindow => write!(f, "not a window"),
+            ManageDecision::Disabled => write!(f, "ignored: disabled"),
+            ManageDecision::NoActivate => write!(f, "ignored: no-activate window"),
+            ManageDecision::Untitled => write!(f, "ignored: no title"),
+            ManageDecision::Cloaked => write!(f, "ignored: cloaked"),
+            ManageDecision::NoClass => write!(f, "ignored: failed to get class name"),
+            ManageDecision::Excluded(name) => write!(f, "ignored: excluded by rule `{}`", name),
+            ManageDecision::Debugged => write!(f, "ignored: debugged process"),
+            ManageDecision::ToolWindow => write!(f, "ignored: tool window"),
+            ManageDecision::Invisible => write!(f, "ignored: invisible"),
+            ManageDecision::UnmanageableParent(reason) => write!(f, "ignored: parent is not managed ({})", reason),
+            ManageDecision::ChildWindow => write!(f, "ignored: owned window without tool or app window style"),
+        }
+    }
 }
 
 #[derive(Default, Debug)]
@@ -757,40 +1236,18 @@ pub struct DwmrApp {
     selected_monitor_index: Option<usize>,
     event_hook: Vec<HWINEVENTHOOK>,
     mouse_hook: Option<HHOOK>,
-}
-
-lazy_static! {
-    static ref DISALLOWED_TITLE: HashSet<String> = HashSet::from([
-        "Windows Shell Experience Host".to_string(),
-        "Microsoft Text Input Application".to_string(),
-        "Action center".to_string(),
-        "New Notification".to_string(),
-        "Date and Time Information".to_string(),
-        "Volume Control".to_string(),
-        "Network Connections".to_string(),
-        "Cortana".to_string(),
-        "Start".to_string(),
-        "Windows Default Lock Screen".to_string(),
-        "Search".to_string(),
-        "WinUI Desktop".to_string()
-    ]);
-
-    static ref DISALLOWED_CLASS: HashSet<String> = HashSet::from([
-        "Windows.UI.Core.CoreWindow".to_string(),
-        "ForegroundStaging".to_string(),
-        "ApplicationManager_DesktopShellWindow".to_string(),
-        "Static".to_string(),
-        "Scrollbar".to_string(),
-        "Progman".to_string(),
-        "OleMainThreadWndClass".to_string(),
-        "Xaml_WindowedPopupClass".to_string(),
-        "LivePreview".to_string(),
-        "TaskListOverlayWnd".to_string(),
-        "Shell_TrayWnd".to_string(),
-        "TopLevelWindowForOverflowXamlIsland".to_string(),
-        "TaskManagerWindow".to_string(),
-    ]);
-
+    keyboard_hook: Option<HHOOK>,
+    hook_keys: Vec<(KeySpec, usize)>,
+    hook_swallowed_key: Option<VIRTUAL_KEY>,
+    shell_hook_message: u32,
+    config_modified: Option<SystemTime>,
+    mode: Option<usize>,
+    pending_spawns: Vec<PendingSpawn>,
+    is_passthrough_toggled: bool,
+    // Index into `passthrough` of the configuration for the focused window.
+    passthrough_index: Option<usize>,
+    // Index into `themes` of the configuration; `None` uses its `theme`.
+    theme_index: Option<usize>,
 }
 
 impl DwmrApp {
@@ -857,9 +1314,16 @@ impl DwmrApp {
         self.event_hook.push(SetWinEventHook(EVENT_SYSTEM_MOVESIZEEND, EVENT_SYSTEM_MOVESIZEEND, None, Some(Self::window_event_hook_proc), 0, 0, WINEVENT_OUTOFCONTEXT));
         self.event_hook.push(SetWinEventHook(EVENT_OBJECT_CLOAKED, EVENT_OBJECT_UNCLOAKED, None, Some(Self::window_event_hook_proc), 0, 0, WINEVENT_OUTOFCONTEXT));
         self.event_hook.push(SetWinEventHook(EVENT_SYSTEM_MINIMIZESTART, EVENT_SYSTEM_MINIMIZEEND, None, Some(Self::window_event_hook_proc), 0, 0, WINEVENT_OUTOFCONTEXT));
+        self.event_hook.push(SetWinEventHook(EVENT_OBJECT_NAMECHANGE, EVENT_OBJECT_NAMECHANGE, None, Some(Self::window_event_hook_proc), 0, 0, WINEVENT_OUTOFCONTEXT));
         self.mouse_hook = Some(SetWindowsHookExW(WH_MOUSE_LL, Some(Self::mouse_event_handler), None, 0)?);
 
-        self.grab_keys()?;
+        self.shell_hook_message = RegisterWindowMessageW(w!("SHELLHOOK"));
+        if self.shell_hook_message == 0 || RegisterShellHookWindow(self.hwnd) == FALSE {
+            println!("Error: failed to register shell hook window - urgency tracking disabled");
+        }
+
+        self.grab_keys();
+        self.update_config_watch();
 
         Ok(())
     }
@@ -897,18 +1361,7 @@ impl DwmrApp {
             }
             WM_HOTKEY => {
                 self.sanitize_monitors();
-                let tag_keys_sub_len = TAG_KEYS.first().unwrap().len();
-                let tag_keys_len = TAG_KEYS.len() * tag_keys_sub_len;
-                if wparam.0 < KEYS.len(){
-                    let key = &KEYS[wparam.0];
-                    (key.func)(self, &key.arg).unwrap();
-                } else if wparam.0 < KEYS.len() + tag_keys_len {
-                    let tag_key_index = wparam.0 - KEYS.len();
-                    let tag_key_first_index = tag_key_index / tag_keys_sub_len;
-                    let tag_key_second_index = tag_key_index % tag_keys_sub_len;
-                    let key = &TAG_KEYS[tag_key_first_index][tag_key_second_index];
-                    (key.func)(self, &key.arg).unwrap();
-                }
+                self.handle_hotkey(wparam.0).unwrap();
                 LRESULT::default()
             }
             WM_UPDATE_DISPLAY => {
@@ -921,10 +1374,72 @@ impl DwmrApp {
                 self.refresh_bar().unwrap();
                 LRESULT::default()
             }
+            WM_RELOAD_CONFIG => {
+                LRESULT(self.reload_config().is_ok() as isize)
+            }
+            WM_TIMER if wparam.0 == MODE_TIMER_ID => {
+                self.exit_mode(&None).unwrap();
+                LRESULT::default()
+            }
+            WM_TIMER if wparam.0 == CONFIG_WATCH_TIMER_ID => {
+                let modified = config_modified_time();
+                if modified.is_some() && modified != self.config_modified {
+                    self.config_modified = modified;
+                    let _ = self.reload_config();
+                }
+                LRESULT::default()
+            }
+            _ if msg == self.shell_hook_message && self.shell_hook_message != 0 => {
+                self.shell_hook(wparam, lparam).unwrap();
+                LRESULT::default()
+            }
             _ => DefWindowProcW(hwnd, msg, wparam, lparam)
         }
     }
 
+    unsafe fn handle_hotkey(&mut self, id: usize) -> Result<()> {
+        let config = config();
+        let mode = self.mode.and_then(|mode_index| config.modes.get(mode_index));
+        let key = match mode {
+            None => config.keys.get(id),
+            Some(_) if id == MODE_EXIT_HOTKEY_ID => return self.exit_mode(&None),
+            Some(mode) => mode.keys.get(id.wrapping_sub(MODE_HOTKEY_ID_BASE)),
+        };
+        let Some(key) = key else {
+            return Ok(());
+        };
+
+        // A chord ends with its key, which may itself enter the next mode.
+        match mode {
+            Some(mode) if mode.is_chord => self.exit_mode(&None)?,
+            Some(mode) if mode.timeout != 0 => {
+                SetTimer(self.hwnd, MODE_TIMER_ID, mode.timeout, None);
+            }
+            _ => {}
+        }
+        (key.func)(self, &key.arg)
+    }
+
+    unsafe fn shell_hook(&mut self, wparam: WPARAM, lparam: LPARAM) -> Result<()> {
+        if wparam.0 as u32 != HSHELL_FLASH {
+            return Ok(());
+        }
+
+        let hwnd = HWND(lparam.0);
+        if hwnd == GetForegroundWindow() {
+            return Ok(());
+        }
+
+        for monitor in self.monitors.iter_mut() {
+            if let Some(client_index) = monitor.find_client_index(&hwnd) {
+                monitor.clients[client_index].is_urgent = true;
+                self.refresh_bar()?;
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
     unsafe extern "system" fn window_event_hook_proc(
         hwin_event_hook: HWINEVENTHOOK,
         event: u32,
@@ -966,30 +1481,19 @@ impl DwmrApp {
             return;
         }
 
-        let mut client_name_buf = [0u16; 256];
-        GetWindowTextW(hwnd, client_name_buf.as_mut());
-        let client_name = PCWSTR::from_raw(client_name_buf.as_ptr()).to_string().unwrap();
-
         let mut class_name_buf = [0u16; 256];
         if GetClassNameW(hwnd, class_name_buf.as_mut()) == 0 {
             SetLastError(WIN32_ERROR(0));
             return;
         }
-        let class_name = PCWSTR::from_raw(class_name_buf.as_ptr()).to_string().unwrap();
         SetLastError(WIN32_ERROR(0));
 
-        let is_disallowed_title = DISALLOWED_TITLE.contains(&client_name);
-        let is_disallowed_class = DISALLOWED_CLASS.contains(&class_name);
-
-        if is_disallowed_title || is_disallowed_class {
-            return;
-        }
-
         self.sanitize_monitors();
 
         match event {
             EVENT_SYSTEM_FOREGROUND => {
                 if hwnd == self.wallpaper_hwnd {
+                    self.update_passthrough();
                     let mut cursor_pos = POINT::default();
                     let _ = GetCursorPos(&mut cursor_pos);
                     if let Some(index) = self.monitors.iter().position(|monitor| -> bool {monitor.is_in_monitor(cursor_pos.x, cursor_pos.y)}) {
@@ -1001,23 +1505,36 @@ impl DwmrApp {
 
                 let is_new_clinet = !self.monitors.iter().any(|monitor| -> bool {monitor.clients.iter().any(|client| -> bool {client.hwnd == hwnd})});
                 if is_new_clinet {
-                    if !Self::is_manageable(&hwnd).unwrap() {
+                    if !Self::is_manageable(&hwnd).unwrap().is_manage() {
+                        self.update_passthrough();
+                        self.refresh_bar().unwrap();
+                        return;
+                    }
+                    let client = self.manage(&hwnd, &snapshot_processes().unwrap_or_default()).unwrap();
+                    self.arrange().unwrap();
+                    if client.is_no_focus {
+                        self.restore_focus().unwrap();
+                        self.refresh_bar().unwrap();
                         return;
                     }
-                    let client = self.manage(&hwnd).unwrap();
-                    self.monitors[client.monitor].arrangemon().unwrap();
                 }
                 self.set_focus(hwnd);
+                self.update_passthrough();
                 self.refresh_bar().unwrap();
             }
             EVENT_OBJECT_UNCLOAKED | EVENT_OBJECT_SHOW => {
                 let is_new_clinet = !self.monitors.iter().any(|monitor| -> bool {monitor.clients.iter().any(|client| -> bool {client.hwnd == hwnd})});
                 if is_new_clinet {
-                    if !Self::is_manageable(&hwnd).unwrap() {
+                    if !Self::is_manageable(&hwnd).unwrap().is_manage() {
+                        return;
+                    }
+                    let client = self.manage(&hwnd, &snapshot_processes().unwrap_or_default()).unwrap();
+                    self.arrange().unwrap();
+                    if client.is_no_focus {
+                        self.restore_focus().unwrap();
+                        self.refresh_bar().unwrap();
                         return;
                     }
-                    let client = self.manage(&hwnd).unwrap();
-                    self.monitors[client.monitor].arrangemon().unwrap();
                 }
                 self.set_focus(hwnd);
                 self.refresh_bar().unwrap();
@@ -1036,11 +1553,11 @@ impl DwmrApp {
             EVENT_SYSTEM_MOVESIZEEND => {
                 let is_new_clinet = !self.monitors.iter().any(|monitor| -> bool {monitor.clients.iter().any(|client| -> bool {client.hwnd == hwnd})});
                 if is_new_clinet {
-                    if !Self::is_manageable(&hwnd).unwrap() {
+                    if !Self::is_manageable(&hwnd).unwrap().is_manage() {
                         return;
                     }
-                    let client = self.manage(&hwnd).unwrap();
-                    self.monitors[client.monitor].arrangemon().unwrap();
+                    self.manage(&hwnd, &snapshot_processes().unwrap_or_default()).unwrap();
+                    self.arrange().unwrap();
                 }
                 self.reallocate_window(&hwnd).unwrap();
                 self.refresh_bar().unwrap();
@@ -1053,12 +1570,29 @@ impl DwmrApp {
                 self.unminimize(&hwnd).unwrap();
                 self.refresh_bar().unwrap();
             }
-            _ => ()
-        }
-    }
-
-    unsafe extern "system" fn mouse_event_handler(ncode: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
-        if ncode < 0 || wparam.0 != WM_LBUTTONDOWN as usize {
+            EVENT_OBJECT_NAMECHANGE => {
+                let is_new_clinet = !self.monitors.iter().any(|monitor| -> bool {monitor.clients.iter().any(|client| -> bool {client.hwnd == hwnd})});
+                if is_new_clinet {
+                    // Windows created without a title are skipped by is_manageable, so retry once they are named.
+                    if !Self::is_manageable(&hwnd).unwrap().is_manage() {
+                        return;
+                    }
+                    let client = self.manage(&hwnd, &snapshot_processes().unwrap_or_default()).unwrap();
+                    self.arrange().unwrap();
+                    if !client.is_no_focus && GetForegroundWindow() == hwnd {
+                        self.set_focus(hwnd);
+                    }
+                    self.refresh_bar().unwrap();
+                    return;
+                }
+                self.update_title(&hwnd).unwrap();
+            }
+            _ => ()
+        }
+    }
+
+    unsafe extern "system" fn mouse_event_handler(ncode: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if ncode < 0 || wparam.0 != WM_LBUTTONDOWN as usize {
             return CallNextHookEx(None, ncode, wparam, lparam);
         }
 
@@ -1077,6 +1611,62 @@ impl DwmrApp {
         return CallNextHookEx(None, ncode, wparam, lparam);
     }
 
+    // Swallows the key presses bound through the hook backend and hands them to `handle_hotkey` like a hotkey.
+    unsafe extern "system" fn keyboard_event_handler(ncode: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if ncode < 0 {
+            return CallNextHookEx(None, ncode, wparam, lparam);
+        }
+
+        let event = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
+        if (event.flags & LLKHF_INJECTED).0 != 0 {
+            return CallNextHookEx(None, ncode, wparam, lparam);
+        }
+
+        let self_hwnd = FindWindowExW(HWND_MESSAGE, None, W_APP_NAME, None);
+        if self_hwnd.0 == 0 {
+            return CallNextHookEx(None, ncode, wparam, lparam);
+        }
+
+        let this = GetWindowLongPtrW(self_hwnd, GWLP_USERDATA) as *mut Self;
+        if this.is_null() {
+            return CallNextHookEx(None, ncode, wparam, lparam);
+        }
+
+        let key = VIRTUAL_KEY(event.vkCode as u16);
+        match wparam.0 as u32 {
+            WM_KEYDOWN | WM_SYSKEYDOWN => {
+                let modifiers = pressed_modifiers();
+                let id = (*this).hook_keys.iter()
+                    .find(|(spec, _)| spec.key == key && spec.modifiers == modifiers)
+                    .map(|(_, id)| *id);
+                if let Some(id) = id {
+                    if (modifiers & (MOD_WIN | MOD_ALT)).0 != 0 {
+                        Self::press_mask_key();
+                    }
+                    let _ = PostMessageW(self_hwnd, WM_HOTKEY, WPARAM(id), LPARAM(0));
+                    (*this).hook_swallowed_key = Some(key);
+                    return LRESULT(1);
+                }
+            }
+            WM_KEYUP | WM_SYSKEYUP if (*this).hook_swallowed_key == Some(key) => {
+                (*this).hook_swallowed_key = None;
+                return LRESULT(1);
+            }
+            _ => {}
+        }
+        CallNextHookEx(None, ncode, wparam, lparam)
+    }
+
+    unsafe fn press_mask_key() {
+        let inputs = [KEYBD_EVENT_FLAGS(0), KEYEVENTF_KEYUP].map(|flags| INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT { wVk: MASK_KEY, dwFlags: flags, ..Default::default() },
+            },
+        });
+        SendInput(&inputs, size_of::<INPUT>() as i32);
+    }
+
     unsafe fn monitor_click_handler(&mut self, mouse_point: &POINT) -> Result<()> {
         let selected_monitor_index = self.monitors.iter().position(|monitor| -> bool {monitor.is_in_monitor(mouse_point.x, mouse_point.y)});
         if selected_monitor_index.is_none() {
@@ -1182,7 +1772,11 @@ impl DwmrApp {
         //client.tags = self.monitors[contained_monitor_index].tagset[self.monitors[contained_monitor_index].selected_tag_index];
         let prev_monitor_tag = self.monitors[contained_monitor_index].tagset[self.monitors[contained_monitor_index].selected_tag_index];
         let monitor_selected_tag_index = self.monitors[contained_monitor_index].selected_tag_index;
-        self.monitors[contained_monitor_index].tagset[monitor_selected_tag_index] = prev_monitor_tag | client.tags;
+        if config().shared_workspaces {
+            client.tags = prev_monitor_tag;
+        } else {
+            self.monitors[contained_monitor_index].tagset[monitor_selected_tag_index] = prev_monitor_tag | client.tags;
+        }
 
         let clients_count = self.monitors[contained_monitor_index].clients.len();
         let master_count = self.monitors[contained_monitor_index].master_count as usize;
@@ -1209,8 +1803,12 @@ impl DwmrApp {
         }
 
         if let Some(selected_monitor_index) = self.selected_monitor_index {
-            if hwnd == self.monitors[selected_monitor_index].selected_hwnd {
-                self.monitors[selected_monitor_index].bar.is_selected_monitor = true;
+            let selected_monitor = &mut self.monitors[selected_monitor_index];
+            if hwnd == selected_monitor.selected_hwnd {
+                selected_monitor.bar.is_selected_monitor = true;
+                if let Some(client_index) = selected_monitor.find_client_index(&hwnd) {
+                    selected_monitor.clients[client_index].is_urgent = false;
+                }
                 return;
             }
         }
@@ -1223,16 +1821,26 @@ impl DwmrApp {
             self.selected_monitor_index = Some(monitor.index);
             monitor.bar.is_selected_monitor = true;
             monitor.selected_hwnd = hwnd;
+            if let Some(client_index) = monitor.find_client_index(&hwnd) {
+                monitor.clients[client_index].is_urgent = false;
+            }
             return;
         } 
     }
 
-    unsafe fn request_update_geom(&mut self) -> Result<()> {
+    unsafe fn show_all_clients(&self) {
         for monitor in self.monitors.iter() {
             for client in monitor.clients.iter() {
                 ShowWindow(client.hwnd, SW_RESTORE);
+                if let Some(swallowed) = &client.swallowed {
+                    ShowWindow(swallowed.hwnd, SW_RESTORE);
+                }
             }
         }
+    }
+
+    unsafe fn request_update_geom(&mut self) -> Result<()> {
+        self.show_all_clients();
 
         self.monitors.clear();
 
@@ -1246,26 +1854,186 @@ impl DwmrApp {
         Ok(())
     }
 
-    unsafe fn grab_keys(&self) -> Result<()> {
+    unsafe fn grab_keys(&mut self) {
         if self.hwnd.0 == 0 {
-            return Ok(());
+            return;
+        }
+
+        for (key_index, key) in config().keys.iter().enumerate() {
+            if !self.is_suspended(key) {
+                self.grab_key(key.spec(), key.backend, key_index);
+            }
         }
+        self.update_keyboard_hook();
+    }
 
-        let mut key_index = 0;
-        for key in KEYS.iter() {
-            RegisterHotKey(self.hwnd, key_index, key.mod_key, key.key as u32)?;
-            key_index += 1;
+    // `toggle_passthrough` stays bound so the passthrough can always be turned off.
+    fn is_suspended(&self, key: &Key) -> bool {
+        if key.action == "toggle_passthrough" {
+            return false;
+        }
+        if self.is_passthrough_toggled {
+            return true;
         }
+        self.passthrough_index
+            .and_then(|index| config().passthrough.get(index).cloned())
+            .is_some_and(|passthrough| passthrough.suspends(&key.spec()))
+    }
+
+    unsafe fn focused_passthrough_index(&self) -> Option<usize> {
+        let focus_hwnd = GetForegroundWindow();
+        let client = self.monitors.iter()
+            .find_map(|monitor| monitor.find_client_index(&focus_hwnd).map(|client_index| &monitor.clients[client_index]))?;
+        config().passthrough.iter().position(|passthrough| passthrough.rule.is_match(client))
+    }
+
+    // Regrabs the regular bindings when the focus moves into or out of a passthrough window.
+    unsafe fn update_passthrough(&mut self) {
+        let passthrough_index = self.focused_passthrough_index();
+        if passthrough_index != self.passthrough_index {
+            self.passthrough_index = passthrough_index;
+            self.regrab_keys();
+        }
+    }
 
-        for tag_keys in TAG_KEYS.iter() {
-            for key in tag_keys.iter() {
-                RegisterHotKey(self.hwnd, key_index, key.mod_key, key.key as u32)?;
-                key_index += 1;
+    // The bindings of an active mode are left alone; `exit_mode` grabs the regular ones again.
+    unsafe fn regrab_keys(&mut self) {
+        if self.mode.is_none() {
+            self.ungrab_keys();
+            self.grab_keys();
+        }
+    }
+
+    // A hotkey taken by another application only warns, so the remaining bindings still work.
+    unsafe fn grab_key(&mut self, spec: KeySpec, backend: Option<KeyBackend>, id: usize) {
+        let backend = backend.unwrap_or(config().key_backend);
+        if backend != KeyBackend::Hook {
+            match RegisterHotKey(self.hwnd, id as i32, spec.modifiers, spec.key.0 as u32) {
+                Ok(()) => return,
+                Err(e) if backend == KeyBackend::HotKey => {
+                    println!("Warning: failed to register {spec} - {e}");
+                    return;
+                }
+                Err(_) => println!("Warning: {spec} is taken, using the keyboard hook"),
+            }
+        }
+        self.hook_keys.push((spec, id));
+    }
+
+    unsafe fn update_keyboard_hook(&mut self) {
+        match self.keyboard_hook {
+            None if !self.hook_keys.is_empty() => {
+                match SetWindowsHookExW(WH_KEYBOARD_LL, Some(Self::keyboard_event_handler), None, 0) {
+                    Ok(hook) => self.keyboard_hook = Some(hook),
+                    Err(e) => println!("Error: failed to install keyboard hook - {e}"),
+                }
             }
+            Some(hook) if self.hook_keys.is_empty() => {
+                let _ = UnhookWindowsHookEx(hook);
+                self.keyboard_hook = None;
+            }
+            _ => {}
         }
+    }
+
+    // Clients, tags and focus stay as they are; only the settings, hotkeys and bars are rebuilt.
+    unsafe fn reload_config(&mut self) -> std::result::Result<(), ConfigError> {
+        let _ = self.exit_mode(&None);
+        let new_config = match config_path() {
+            Some(path) if path.exists() => Config::load(&path),
+            Some(path) => Err(ConfigError::new(format!("{} does not exist", path.display()))),
+            None => Ok(Config::default()),
+        };
+        let new_config = match new_config {
+            Ok(new_config) => new_config,
+            Err(e) => {
+                println!("Error: failed to reload configuration, keeping the current one - {e}");
+                return Err(e);
+            }
+        };
+
+        self.ungrab_keys();
+        set_config(new_config);
+        self.passthrough_index = self.focused_passthrough_index();
+        self.grab_keys();
+        self.update_config_watch();
+
+        let config = config();
+        let tagmask = config.tagmask();
+        self.theme_index = None;
+        let theme = self.current_theme();
+        for monitor in self.monitors.iter_mut() {
+            monitor.attach = config.monitor_attach.iter()
+                .find(|(index, _)| *index == monitor.index)
+                .map_or(config.default_attach, |(_, attach)| *attach);
+
+            for tags in monitor.tagset.iter_mut() {
+                *tags = mask_tags(*tags, tagmask, 1);
+            }
+            let selected_tags = monitor.tagset[monitor.selected_tag_index];
+            for client in monitor.clients.iter_mut() {
+                client.tags = mask_tags(client.tags, tagmask, selected_tags);
+            }
+
+            monitor.bar.theme = theme.clone();
+            if monitor.bar.hwnd.0 != 0 {
+                if let Err(e) = monitor.bar.apply_style() {
+                    println!("Error: failed to restyle bar - {e}");
+                }
+            }
+        }
+
+        self.arrange().map_err(|e| ConfigError::new(e.to_string()))?;
+        self.refresh_bar().map_err(|e| ConfigError::new(e.to_string()))?;
+        self.autostart(true);
         Ok(())
     }
 
+    unsafe fn update_config_watch(&mut self) {
+        if self.hwnd.0 == 0 {
+            return;
+        }
+
+        if config().watch_config {
+            self.config_modified = config_modified_time();
+            SetTimer(self.hwnd, CONFIG_WATCH_TIMER_ID, CONFIG_WATCH_INTERVAL_MS, None);
+        } else {
+            let _ = KillTimer(self.hwnd, CONFIG_WATCH_TIMER_ID);
+        }
+    }
+
+    unsafe fn grab_mode_keys(&mut self, mode: &Mode) {
+        for (key_index, key) in mode.keys.iter().enumerate() {
+            self.grab_key(key.spec(), key.backend, MODE_HOTKEY_ID_BASE + key_index);
+        }
+
+        let escape = KeySpec::new(HOT_KEY_MODIFIERS(0), VK_ESCAPE);
+        if !mode.keys.iter().any(|key| key.spec() == escape) {
+            self.grab_key(escape, None, MODE_EXIT_HOTKEY_ID);
+        }
+        self.update_keyboard_hook();
+    }
+
+    unsafe fn ungrab_mode_keys(&mut self, mode: &Mode) {
+        for key_index in 0..mode.keys.len() {
+            let _ = UnregisterHotKey(self.hwnd, (MODE_HOTKEY_ID_BASE + key_index) as i32);
+        }
+        let _ = UnregisterHotKey(self.hwnd, MODE_EXIT_HOTKEY_ID as i32);
+        self.hook_keys.clear();
+    }
+
+    // The keyboard hook stays installed until the next `update_keyboard_hook`.
+    unsafe fn ungrab_keys(&mut self) {
+        if self.hwnd.0 == 0 {
+            return;
+        }
+
+        for key_index in 0..config().keys.len() {
+            let _ = UnregisterHotKey(self.hwnd, key_index as i32);
+        }
+        self.hook_keys.clear();
+    }
+
     unsafe extern "system" fn update_geom(hmonitor: HMONITOR, _: HDC, _: *mut RECT, lparam: LPARAM) -> BOOL {
         let mut monitor_info = MONITORINFOEXW{
             monitorInfo: MONITORINFO {
@@ -1282,20 +2050,34 @@ impl DwmrApp {
 
         let this = lparam.0 as *mut DwmrApp;
 
+        let monitor_index = (*this).monitors.len();
+        let config = config();
+        let initial_tags = if config.shared_workspaces {
+            1 << (monitor_index % config.tags.len())
+        } else {
+            1
+        };
+
+        let attach = config.monitor_attach.iter()
+            .find(|(index, _)| *index == monitor_index)
+            .map_or(config.default_attach, |(_, attach)| *attach);
+
         let mut monitor = Monitor{
             name: monitor_info.szDevice,
-            index: (*this).monitors.len(),
+            index: monitor_index,
             rect: Rect::from_win_rect(&monitor_info.monitorInfo.rcMonitor),
             client_area: Rect::from_win_rect(&monitor_info.monitorInfo.rcWork),
             master_count: 1,
             master_factor: 0.5,
-            tagset: [1, 1],
+            tagset: [initial_tags, initial_tags],
+            attach,
             ..Default::default()
         };
 
         monitor.client_area.y += BAR_HEIGHT as i32;
         monitor.client_area.height -= BAR_HEIGHT as i32;
-        monitor.bar.selected_tags = 1;
+        monitor.bar.selected_tags = initial_tags;
+        monitor.bar.theme = (*this).current_theme();
 
         let display_rect = monitor.rect.clone();
         (*this).monitors.push(monitor);
@@ -1339,9 +2121,14 @@ impl DwmrApp {
         //new_client.tags = self.monitors[target_monitor_index].tagset[self.monitors[target_monitor_index].selected_tag_index];
         let prev_monitor_tag = self.monitors[target_monitor_index].tagset[self.monitors[target_monitor_index].selected_tag_index];
         let monitor_selected_tag_index = self.monitors[target_monitor_index].selected_tag_index;
-        self.monitors[target_monitor_index].tagset[monitor_selected_tag_index] = prev_monitor_tag | new_client.tags;
+        if config().shared_workspaces {
+            new_client.tags = prev_monitor_tag;
+        } else {
+            self.monitors[target_monitor_index].tagset[monitor_selected_tag_index] = prev_monitor_tag | new_client.tags;
+        }
         new_client.monitor = target_monitor_index;
-        self.monitors[target_monitor_index].clients.push(new_client);
+        let target_attach = self.monitors[target_monitor_index].attach;
+        self.monitors[target_monitor_index].attach(new_client, target_attach);
 
         self.selected_monitor_index = Some(target_monitor_index);
         self.monitors[target_monitor_index].selected_hwnd = new_client_hwnd;
@@ -1395,7 +2182,11 @@ impl DwmrApp {
 
     unsafe fn refresh_bar(&mut self) -> Result<()> {
         let selected_monitor_index = self.selected_monitor_index;
+        let mode_name = self.mode.and_then(|mode_index| config().modes.get(mode_index).map(|mode| mode.name.clone()));
+        let is_passthrough = self.is_passthrough_toggled || self.passthrough_index.is_some();
         for monitor in self.monitors.iter_mut() {
+            monitor.bar.mode = mode_name.clone();
+            monitor.bar.is_passthrough = is_passthrough;
             let is_selected_monitor = selected_monitor_index.is_some() && monitor.index == selected_monitor_index.unwrap();
             monitor.update_bar(is_selected_monitor);
         }
@@ -1419,7 +2210,15 @@ impl DwmrApp {
     }
 
     pub unsafe fn scan(&mut self) -> Result<()> {
-        EnumWindows(Some(Self::scan_enum), LPARAM(self as *mut _ as isize))?;
+        let mut hwnds: Vec<HWND> = Vec::new();
+        EnumWindows(Some(inspector::collect_windows), LPARAM(&mut hwnds as *mut _ as isize))?;
+
+        let processes = snapshot_processes().unwrap_or_default();
+        for hwnd in hwnds.iter() {
+            if Self::is_manageable(hwnd)?.is_manage() {
+                self.manage(hwnd, &processes)?;
+            }
+        }
 
         self.refresh_current_focus()?;
         let selected_monitor = &mut self.monitors[self.selected_monitor_index.unwrap()];
@@ -1437,20 +2236,6 @@ impl DwmrApp {
         Ok(())
     }
 
-    unsafe extern "system" fn scan_enum(hwnd: HWND, lparam: LPARAM) -> BOOL {
-        if !Self::is_manageable(&hwnd).unwrap() {
-            return TRUE;
-        }
-
-        let this = lparam.0 as *mut Self;
-        if this.is_null() {
-            return TRUE;
-        }
-
-        (*this).manage(&hwnd).unwrap();
-        TRUE
-    }
-
     unsafe fn is_cloaked(hwnd: &HWND) -> Result<bool> {
         let mut cloaked_val = 0;
         DwmGetWindowAttribute(*hwnd, DWMWA_CLOAKED, (&mut cloaked_val) as *const _ as *mut _, size_of::<u32>() as u32)?;
@@ -1483,43 +2268,33 @@ impl DwmrApp {
         }
     }
 
-    unsafe fn is_manageable(hwnd: &HWND) -> Result<bool> {
+    unsafe fn is_manageable(hwnd: &HWND) -> Result<ManageDecision> {
         if IsWindow(*hwnd) == FALSE {
-            return Ok(false);
+            return Ok(ManageDecision::NotWindow);
         }
 
         let style = GetWindowLongW(*hwnd, GWL_STYLE) as u32;
         if has_flag!(style, WS_DISABLED.0) {
-            return Ok(false);
+            return Ok(ManageDecision::Disabled);
         }
 
         let exstyle = GetWindowLongW(*hwnd, GWL_EXSTYLE) as u32;
         if has_flag!(exstyle, WS_EX_NOACTIVATE.0) {
-            return Ok(false);
+            return Ok(ManageDecision::NoActivate);
         }
 
         SetLastError(WIN32_ERROR(0));
         let name_length = GetWindowTextLengthW(*hwnd);
         if name_length == 0 {
             GetLastError()?;
-            return Ok(false);
+            return Ok(ManageDecision::Untitled);
         }
 
         if Self::is_cloaked(hwnd)? {
-            return Ok(false);
+            return Ok(ManageDecision::Cloaked);
         }
 
-        let mut client_name_buf = [0u16; 256];
-        SetLastError(WIN32_ERROR(0));
-        if GetWindowTextW(*hwnd, client_name_buf.as_mut()) == 0 {
-            if let Err(e) = GetLastError() {
-                println!("Error: failed to get window title - {e}");
-            }
-        }
-        let client_name = PCWSTR::from_raw(client_name_buf.as_ptr()).to_string().unwrap();
-        if DISALLOWED_TITLE.contains(&client_name) {
-            return Ok(false);
-        }
+        let title = Self::get_window_title(hwnd);
 
         let mut class_name_buf = [0u16; 256];
         SetLastError(WIN32_ERROR(0));
@@ -1527,16 +2302,34 @@ impl DwmrApp {
             if let Err(e) = GetLastError() {
                 println!("Error: failed to get class name - {e}");
             }
-            return Ok(false); 
+            return Ok(ManageDecision::NoClass); 
         }
-        let class_name = PCWSTR::from_raw(class_name_buf.as_ptr()).to_string().unwrap();
-        if DISALLOWED_CLASS.contains(&class_name) {
-            return Ok(false);
+        let class = PCWSTR::from_raw(class_name_buf.as_ptr()).to_string().unwrap();
+
+        let config = config();
+        let needs_process_name = config.exclude_rules.iter().any(|rule| rule.process_name.is_some());
+        let process_name = match needs_process_name {
+            true => get_process_id(hwnd)
+                .and_then(|process_id| get_process_path(process_id))
+                .map(|process_path| file_basename(&process_path).to_string())
+                .unwrap_or_default(),
+            false => String::default()
+        };
+
+        let window = WindowProperties {
+            title,
+            class,
+            process_name,
+            style,
+            exstyle,
+        };
+        if let Some(rule) = config.exclude_rules.iter().find(|rule| rule.is_match(&window)) {
+            return Ok(ManageDecision::Excluded(rule.name.clone()));
         }
 
-        if EXCLUDE_DEBUGGED_WINDOW {
+        if config.exclude_debugged_window {
             if Self::is_debugged(hwnd)? {
-                return Ok(false);
+                return Ok(ManageDecision::Debugged);
             }
         }
 
@@ -1546,23 +2339,26 @@ impl DwmrApp {
 
         if !parent_exist {
             if is_tool {
-                return Ok(false);
+                return Ok(ManageDecision::ToolWindow);
+            } else if IsWindowVisible(*hwnd) == TRUE {
+                return Ok(ManageDecision::Manage);
             } else {
-                let result = IsWindowVisible(*hwnd) == TRUE;
-                return Ok(result);
+                return Ok(ManageDecision::Invisible);
             }
         }
 
-        if Self::is_manageable(&parent)? == false {
-            return Ok(false);
+        let parent_decision = Self::is_manageable(&parent)?;
+        if !parent_decision.is_manage() {
+            return Ok(ManageDecision::UnmanageableParent(Box::new(parent_decision)));
         }
 
         let is_app = has_flag!(exstyle, WS_EX_APPWINDOW.0);
-        if is_tool || is_app {
-            return Ok(true);
+        let is_dialog = config.float_owned_windows && config.dialog_classes.contains(&window.class);
+        if is_tool || is_app || is_dialog {
+            return Ok(ManageDecision::Manage);
         }
 
-        Ok(false)
+        Ok(ManageDecision::ChildWindow)
     }
 
     unsafe fn get_root(hwnd: &HWND) -> Result<HWND> {
@@ -1578,7 +2374,7 @@ impl DwmrApp {
         Ok(current)
     }
 
-    unsafe fn manage(&mut self, hwnd: &HWND) -> Result<Client> {
+    unsafe fn manage(&mut self, hwnd: &HWND, processes: &HashMap<u32, ProcessEntry>) -> Result<Client> {
         for monitor in self.monitors.iter() {
             if let Some(client_index) = monitor.find_client_index(hwnd) {
                 return Ok(monitor.clients[client_index].clone());
@@ -1592,8 +2388,6 @@ impl DwmrApp {
 
         GetWindowInfo(*hwnd, &mut window_info)?;
 
-        let parent = GetParent(*hwnd);
-        let root = Self::get_root(hwnd)?;
         let is_cloaked = Self::is_cloaked(hwnd)?;
         let is_minimized = IsIconic(*hwnd) == TRUE;
         let rect = Rect::from_win_rect(&window_info.rcWindow);
@@ -1609,84 +2403,227 @@ impl DwmrApp {
             }
         }
 
-        let mut client_name_buf = [0u16; 256];
-        SetLastError(WIN32_ERROR(0));
-        if GetWindowTextW(*hwnd, client_name_buf.as_mut()) == 0 {
-            if let Err(e) = GetLastError() {
-                println!("Error: failed to get window title - {e}");
-            }
+        let window_client = Client::from_window(hwnd, processes)?;
+        if window_client.process_path.is_empty() {
+            println!("Error: Failed to get process of {}", window_client);
         }
-        let title = PCWSTR::from_raw(client_name_buf.as_ptr()).to_string().unwrap();
 
-        let mut class_name_buf = [0u16; 256];
-        SetLastError(WIN32_ERROR(0));
-        if GetClassNameW(*hwnd, class_name_buf.as_mut()) == 0 {
-            if let Err(e) = GetLastError() {
-                println!("Error: failed to get class name - {e}");
-            }
+        let config = config();
+        let initial_tags = if config.shared_workspaces {
+            self.monitors[monitor_index].tagset[self.monitors[monitor_index].selected_tag_index]
+        } else {
+            config.default_tags
+        };
+
+        let mut client = Client {
+            rect: rect.into(),
+            bw: 0,
+            is_minimized,
+            is_cloaked,
+            monitor: monitor_index,
+            tags: initial_tags,
+            ..window_client
+        };
+
+        let owner = [client.parent, client.root].into_iter()
+            .filter(|owner| owner.0 != 0 && owner != hwnd)
+            .find_map(|owner| self.find_client(&owner));
+        client.inherit_owner(owner, &config);
+
+        let mut resolved_rule = Rule::resolve(config.rules.iter(), &client);
+        if let Some(spawn_rule) = self.take_spawn_rule(&client, processes) {
+            resolved_rule.get_or_insert_with(Rule::default).merge(&spawn_rule);
+        }
+        let rule = resolved_rule.as_ref();
+        let mut rule_monitor_index = None;
+        if let Some(rule) = rule {
+            rule.apply(&mut client);
+            rule_monitor_index = rule.monitor.as_ref().and_then(|target| self.find_monitor(target));
         }
-        let class = PCWSTR::from_raw(class_name_buf.as_ptr()).to_string().unwrap();
 
-        let get_processname = || -> Result<String> {
-            let mut process_id: u32 = 0;
-            if GetWindowThreadProcessId(*hwnd, Some(&mut process_id as *mut _)) == 0 {
-                if let Err(e) = GetLastError() {
-                    println!("Error: Failed to get process id - {}", e);
-                    return Err(e);
-                }
-                return Ok(String::default());
+        if let Some(rule_monitor_index) = rule_monitor_index {
+            client.monitor = rule_monitor_index;
+        }
+
+        let no_swallow = client.is_floating || rule.is_some_and(|rule| rule.no_swallow == Some(true));
+        if !no_swallow {
+            if let Some((terminal_monitor_index, terminal_index)) = self.find_swallow_terminal(&client, processes) {
+                self.swallow(&mut client, terminal_monitor_index, terminal_index);
+                return Ok(client);
             }
+        }
 
-            let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, process_id);
-            if let Err(e) = handle {
-                println!("Error: Failed to open process handle - {}", e);
-                return Err(e);
+        let switch_to_tag = rule.is_some_and(|rule| rule.switch_to_tag == Some(true));
+        let monitor_tag_index = self.monitors[client.monitor].selected_tag_index;
+        if config.shared_workspaces {
+            if rule_monitor_index.is_some() {
+                client.tags = self.monitors[client.monitor].tagset[monitor_tag_index];
+            } else if let Some(workspace_monitor_index) = self.find_workspace_monitor(client.tags) {
+                client.monitor = workspace_monitor_index;
             }
+        } else if !switch_to_tag {
+            let prev_monitor_tags = self.monitors[client.monitor].tagset[monitor_tag_index];
+            self.monitors[client.monitor].tagset[monitor_tag_index] = client.tags | prev_monitor_tags;
+        }
+
+        if client.is_floating {
+            self.place_floating_client(&mut client, monitor_index, rule)?;
+        }
 
-            let handle = handle.unwrap();
-            let mut file_name_buf = [0u16; 256];
-            if GetProcessImageFileNameW(handle, &mut file_name_buf) == 0 {
-                if let Err(e) = GetLastError() {
-                    println!("Error: Failed to get file name - {}", e);
+        let attach = rule.and_then(|rule| rule.attach).unwrap_or(self.monitors[client.monitor].attach);
+        self.monitors[client.monitor].attach(client.clone(), attach);
+
+        if switch_to_tag {
+            let monitor = &mut self.monitors[client.monitor];
+            let previous_tags = monitor.tagset[monitor.selected_tag_index];
+            if (client.tags & tagmask()) != 0 && (client.tags & tagmask()) != previous_tags {
+                monitor.selected_tag_index ^= 1;
+                monitor.tagset[monitor.selected_tag_index] = client.tags & tagmask();
+                if config.shared_workspaces {
+                    self.swap_workspaces(client.monitor, previous_tags);
                 }
             }
-            let file_name = PCWSTR::from_raw(file_name_buf.as_ptr()).to_string().unwrap();
-            CloseHandle(handle)?;
-            return Ok(file_name);
-        };
+        }
 
-        let process_filename = get_processname().unwrap_or_default();
-        let monitor_tag_index = self.monitors[monitor_index].selected_tag_index;
+        Ok(client)
+    }
 
-        let mut client = Client {
-            hwnd: *hwnd,
-            title,
-            class,
-            process_filename,
-            parent,
-            root,
-            rect: rect.into(),
-            bw: 0,
-            is_minimized,
-            is_cloaked,
-            monitor: monitor_index,
-            tags: DEFAULT_TAGS,
+    // The placement of a `spawn` binding applies to the first window of the process or one of its children.
+    unsafe fn take_spawn_rule(&mut self, client: &Client, processes: &HashMap<u32, ProcessEntry>) -> Option<Rule> {
+        self.pending_spawns.retain(|pending_spawn| !pending_spawn.is_expired());
+        if self.pending_spawns.is_empty() || client.process_id == 0 {
+            return None;
+        }
+
+        let mut process_ids = vec![client.process_id];
+        process_ids.extend(get_ancestor_process_ids(processes, client.process_id));
+        let index = self.pending_spawns.iter().position(|pending_spawn| process_ids.contains(&pending_spawn.process_id))?;
+        let pending_spawn = self.pending_spawns.remove(index);
+
+        Some(Rule {
+            tags: pending_spawn.tags,
+            monitor: pending_spawn.monitor,
             ..Default::default()
-        };
+        })
+    }
 
-        for rule in RULES.iter() {
-            if rule.is_match(&client) {
-                client.is_floating = rule.is_floating;
-                client.tags = rule.tags;
-                break;
+    fn is_terminal(client: &Client) -> bool {
+        config().swallow_terminals.iter().any(|terminal| terminal.eq_ignore_ascii_case(&client.process_name))
+    }
+
+    // A client swallows the nearest managed terminal among the ancestors of its process.
+    unsafe fn find_swallow_terminal(&self, client: &Client, processes: &HashMap<u32, ProcessEntry>) -> Option<(usize, usize)> {
+        if config().swallow_terminals.is_empty() || client.process_id == 0 || Self::is_terminal(client) {
+            return None;
+        }
+
+        get_ancestor_process_ids(processes, client.process_id)
+            .into_iter()
+            .find_map(|process_id| self.monitors.iter().find_map(|monitor| {
+                monitor.clients.iter()
+                    .position(|terminal| terminal.process_id == process_id && terminal.swallowed.is_none() && Self::is_terminal(terminal))
+                    .map(|terminal_index| (monitor.index, terminal_index))
+            }))
+    }
+
+    unsafe fn swallow(&mut self, client: &mut Client, monitor_index: usize, terminal_index: usize) {
+        let monitor = &mut self.monitors[monitor_index];
+        let mut terminal = monitor.clients.remove(terminal_index);
+        terminal.is_hide = true;
+        ShowWindow(terminal.hwnd, SW_HIDE);
+
+        client.monitor = monitor_index;
+        client.tags = terminal.tags;
+        if monitor.selected_hwnd == terminal.hwnd {
+            monitor.selected_hwnd = client.hwnd;
+        }
+        client.swallowed = Some(Box::new(terminal));
+        monitor.clients.insert(terminal_index, client.clone());
+    }
+
+    fn find_client(&self, hwnd: &HWND) -> Option<&Client> {
+        self.monitors.iter()
+            .flat_map(|monitor| monitor.clients.iter())
+            .find(|client| client.hwnd == *hwnd)
+    }
+
+    fn find_monitor(&self, target: &MonitorTarget) -> Option<usize> {
+        match target {
+            MonitorTarget::Index(index) => (*index < self.monitors.len()).then_some(*index),
+            MonitorTarget::Name(name) => {
+                let name = name.to_lowercase();
+                self.monitors.iter().position(|monitor| monitor.name_string().to_lowercase().ends_with(&name))
             }
         }
+    }
 
-        let prev_monitor_tags = self.monitors[monitor_index].tagset[monitor_tag_index];
-        self.monitors[monitor_index].tagset[monitor_tag_index] = client.tags | prev_monitor_tags;
-        self.monitors[monitor_index].clients.push(client.clone());
+    unsafe fn place_floating_client(&self, client: &mut Client, source_monitor_index: usize, rule: Option<&Rule>) -> Result<()> {
+        let monitor = &self.monitors[client.monitor];
+        let source_monitor = &self.monitors[source_monitor_index];
+        let mut rect = client.rect.clone();
+        if let Some(rule_rect) = rule.and_then(|rule| rule.rect) {
+            rect = rule_rect.resolve(&monitor.client_area);
+        } else if client.owner.0 != 0 {
+            let mut owner_rect = RECT::default();
+            GetWindowRect(client.owner, &mut owner_rect)?;
+            rect = rect.centered_in(&Rect::from_win_rect(&owner_rect));
+        } else {
+            rect.x += monitor.rect.x - source_monitor.rect.x;
+            rect.y += monitor.rect.y - source_monitor.rect.y;
+        }
 
-        Ok(client)
+        if rule.is_some_and(|rule| rule.is_centered == Some(true)) {
+            rect = rect.centered_in(&monitor.client_area);
+        }
+
+        if rect == client.rect {
+            return Ok(());
+        }
+
+        SetWindowPos(client.hwnd, None, rect.x, rect.y, rect.width, rect.height, SWP_NOZORDER | SWP_NOACTIVATE)?;
+        client.rect = rect;
+        Ok(())
+    }
+
+    unsafe fn get_window_title(hwnd: &HWND) -> String {
+        let mut client_name_buf = [0u16; 256];
+        SetLastError(WIN32_ERROR(0));
+        if GetWindowTextW(*hwnd, client_name_buf.as_mut()) == 0 {
+            if let Err(e) = GetLastError() {
+                println!("Error: failed to get window title - {e}");
+            }
+        }
+        PCWSTR::from_raw(client_name_buf.as_ptr()).to_string().unwrap_or_default()
+    }
+
+    unsafe fn update_title(&mut self, hwnd: &HWND) -> Result<()> {
+        let title = Self::get_window_title(hwnd);
+        for monitor in self.monitors.iter_mut() {
+            let client_index = monitor.find_client_index(hwnd);
+            if client_index.is_none() {
+                continue;
+            }
+
+            let client = &mut monitor.clients[client_index.unwrap()];
+            if client.title == title {
+                return Ok(());
+            }
+
+            let previous_client = client.clone();
+            client.title = title;
+
+            let config = config();
+            let newly_matching_rules = config.rules.iter().filter(|rule| -> bool {
+                rule.reapply_on_title_change && !rule.is_match(&previous_client)
+            });
+            if let Some(rule) = Rule::resolve(newly_matching_rules, client) {
+                rule.apply(client);
+                self.arrange()?;
+                self.refresh_bar()?;
+            }
+            return Ok(());
+        }
+        Ok(())
     }
 
     unsafe fn unmanage(&mut self, hwnd: &HWND) -> Result<()> {
@@ -1694,7 +2631,16 @@ impl DwmrApp {
             let found_index = monitor.find_client_index(hwnd);
 
             if let Some(index) = found_index {
-                monitor.clients.remove(index);
+                let client = monitor.clients.remove(index);
+                if let Some(mut swallowed) = client.swallowed {
+                    // Put the swallowed terminal back in the slot of the window that took it over.
+                    swallowed.tags = client.tags;
+                    swallowed.monitor = monitor.index;
+                    if monitor.selected_hwnd == client.hwnd {
+                        monitor.selected_hwnd = swallowed.hwnd;
+                    }
+                    monitor.clients.insert(index, *swallowed);
+                }
                 monitor.arrangemon()?;
                 return Ok(());
             }
@@ -1703,7 +2649,125 @@ impl DwmrApp {
         Ok(())
     }
 
+    fn find_workspace_monitor(&self, tags: u32) -> Option<usize> {
+        self.monitors.iter().position(|monitor| (monitor.tagset[monitor.selected_tag_index] & tags) != 0)
+    }
+
+    // Shared workspaces: a client lives on whichever monitor currently shows one of its tags.
+    unsafe fn sync_workspaces(&mut self) -> Result<()> {
+        if !config().shared_workspaces {
+            return Ok(());
+        }
+
+        for source_index in 0..self.monitors.len() {
+            let source_monitor = &self.monitors[source_index];
+            let source_visible_tags = source_monitor.tagset[source_monitor.selected_tag_index];
+            let mut client_index = 0;
+            while client_index < self.monitors[source_index].clients.len() {
+                let client_tags = self.monitors[source_index].clients[client_index].tags;
+                let is_sticky = self.monitors[source_index].clients[client_index].is_sticky;
+                let target_index = self.find_workspace_monitor(client_tags);
+                if is_sticky || (client_tags & source_visible_tags) != 0 || target_index.is_none() {
+                    client_index += 1;
+                    continue;
+                }
+
+                let target_index = target_index.unwrap();
+                let mut client = self.monitors[source_index].clients.remove(client_index);
+                if self.monitors[source_index].selected_hwnd == client.hwnd {
+                    self.monitors[source_index].selected_hwnd = HWND(0);
+                }
+
+                self.move_floating_client(&mut client, source_index, target_index)?;
+                client.monitor = target_index;
+                self.monitors[target_index].clients.push(client);
+            }
+        }
+        Ok(())
+    }
+
+    // Owned windows such as dialogs follow their owner's tags and monitor.
+    unsafe fn sync_owned_clients(&mut self) -> Result<()> {
+        if !config().float_owned_windows {
+            return Ok(());
+        }
+
+        let owners: HashMap<isize, (u32, usize)> = self.monitors.iter()
+            .flat_map(|monitor| monitor.clients.iter().map(move |client| (client.hwnd.0, (client.tags, monitor.index))))
+            .collect();
+
+        for source_index in 0..self.monitors.len() {
+            let mut client_index = 0;
+            while client_index < self.monitors[source_index].clients.len() {
+                let client = &mut self.monitors[source_index].clients[client_index];
+                let owner = owners.get(&client.owner.0).filter(|_| client.owner.0 != 0);
+                if owner.is_none() {
+                    client_index += 1;
+                    continue;
+                }
+
+                let (owner_tags, target_index) = *owner.unwrap();
+                client.tags = owner_tags;
+                if target_index == source_index {
+                    client_index += 1;
+                    continue;
+                }
+
+                let mut client = self.monitors[source_index].clients.remove(client_index);
+                if self.monitors[source_index].selected_hwnd == client.hwnd {
+                    self.monitors[source_index].selected_hwnd = HWND(0);
+                }
+
+                self.move_floating_client(&mut client, source_index, target_index)?;
+                client.monitor = target_index;
+                self.monitors[target_index].clients.push(client);
+            }
+        }
+        Ok(())
+    }
+
+    unsafe fn move_floating_client(&self, client: &mut Client, source_index: usize, target_index: usize) -> Result<()> {
+        if !client.is_floating {
+            return Ok(());
+        }
+
+        let source_rect = &self.monitors[source_index].rect;
+        let target_rect = &self.monitors[target_index].rect;
+        let mut window_rect = RECT::default();
+        GetWindowRect(client.hwnd, &mut window_rect)?;
+        client.rect = Rect::from_win_rect(&window_rect);
+        client.rect.x += target_rect.x - source_rect.x;
+        client.rect.y += target_rect.y - source_rect.y;
+        SetWindowPos(client.hwnd, None, client.rect.x, client.rect.y, 0, 0, SWP_NOSIZE | SWP_NOZORDER | SWP_NOACTIVATE)?;
+        Ok(())
+    }
+
+    // Shared workspaces: hand the previously shown tags to any monitor that was showing the newly viewed ones.
+    fn swap_workspaces(&mut self, monitor_index: usize, previous_tags: u32) {
+        let monitor = &self.monitors[monitor_index];
+        let visible_tags = monitor.tagset[monitor.selected_tag_index];
+        let mut previous_tags = Some(previous_tags);
+        for other_monitor in self.monitors.iter_mut() {
+            let other_visible_tags = other_monitor.tagset[other_monitor.selected_tag_index];
+            if other_monitor.index == monitor_index || (other_visible_tags & visible_tags) == 0 {
+                continue;
+            }
+
+            let remaining_tags = other_visible_tags & !visible_tags;
+            let new_tags = match previous_tags.take() {
+                Some(tags) => tags,
+                None if remaining_tags != 0 => remaining_tags,
+                None => continue,
+            };
+
+            other_monitor.selected_tag_index ^= 1;
+            other_monitor.tagset[other_monitor.selected_tag_index] = new_tags;
+        }
+    }
+
     pub unsafe fn arrange(&mut self) -> Result<()> {
+        self.sync_owned_clients()?;
+        self.sync_workspaces()?;
         for monitor in self.monitors.iter_mut() {
             monitor.arrangemon()?;
         }
@@ -1712,6 +2776,8 @@ impl DwmrApp {
     }
 
     pub unsafe fn cleanup(&mut self) -> Result<()> {
+        self.exit_mode(&None)?;
+
         for event_hook in self.event_hook.iter() {
             if event_hook.0 != 0 {
                 UnhookWinEvent(*event_hook);
@@ -1719,26 +2785,25 @@ impl DwmrApp {
         }
         self.event_hook.clear();
 
-        let monitors = &self.monitors;
-        for monitor in monitors.iter() {
-            for client in monitor.clients.iter() {
-                ShowWindow(client.hwnd, SW_RESTORE);
-            }
-        }
+        self.show_all_clients();
 
         if self.mouse_hook.is_some() {
             let _ = UnhookWindowsHookEx(self.mouse_hook.unwrap());
             self.mouse_hook = None;
         }
 
+        if self.shell_hook_message != 0 && self.hwnd.0 != 0 {
+            DeregisterShellHookWindow(self.hwnd);
+            self.shell_hook_message = 0;
+        }
+
         if self.hwnd.0 == 0 {
             return Ok(());
         }
 
-        let tag_keys_len = TAG_KEYS.len() * TAG_KEYS.first().unwrap().len();
-        for key_index in 0..(KEYS.len() + tag_keys_len) {
-            UnregisterHotKey(self.hwnd, key_index as i32)?;
-        }
+        let _ = KillTimer(self.hwnd, CONFIG_WATCH_TIMER_ID);
+        self.ungrab_keys();
+        self.update_keyboard_hook();
 
         self.hwnd = HWND::default();
 
@@ -1763,18 +2828,23 @@ impl DwmrApp {
 
         let monitor_index = self.selected_monitor_index.unwrap();
         let monitor = &mut self.monitors[monitor_index];
-        if (selected_tag & TAGMASK) == monitor.tagset[monitor.selected_tag_index] {
+        if (selected_tag & tagmask()) == monitor.tagset[monitor.selected_tag_index] {
             return Ok(());
         }
 
         self.all_unminimize(arg)?;
 
         let monitor = &mut self.monitors[monitor_index];
+        let previous_tags = monitor.tagset[monitor.selected_tag_index];
         monitor.selected_tag_index ^= 1;
-        if (selected_tag & TAGMASK) != 0 {
-            monitor.tagset[monitor.selected_tag_index] = selected_tag & TAGMASK;
+        if (selected_tag & tagmask()) != 0 {
+            monitor.tagset[monitor.selected_tag_index] = selected_tag & tagmask();
         }
         monitor.update_bar(monitor.bar.is_selected_monitor);
+        if config().shared_workspaces {
+            self.swap_workspaces(monitor_index, previous_tags);
+            self.sync_workspaces()?;
+        }
         self.refresh_focus()?;
         self.arrange()?;
         self.refresh_bar()?;
@@ -1790,17 +2860,37 @@ impl DwmrApp {
 
         let monitor_index = self.selected_monitor_index.unwrap();
         let monitor = &mut self.monitors[monitor_index];
-        let new_tag_set = (selected_tag & TAGMASK) ^ monitor.tagset[monitor.selected_tag_index];
+        let new_tag_set = (selected_tag & tagmask()) ^ monitor.tagset[monitor.selected_tag_index];
 
         if new_tag_set == 0 {
             return Ok(());
         }
 
+        if config().shared_workspaces {
+            let added_tags = new_tag_set & !monitor.tagset[monitor.selected_tag_index];
+            let is_stealing_whole_workspace = self.monitors.iter().any(|other_monitor| -> bool {
+                let other_visible_tags = other_monitor.tagset[other_monitor.selected_tag_index];
+                other_monitor.index != monitor_index && (other_visible_tags & added_tags) != 0 && (other_visible_tags & !added_tags) == 0
+            });
+            if is_stealing_whole_workspace {
+                return Ok(());
+            }
+
+            for other_monitor in self.monitors.iter_mut() {
+                if other_monitor.index != monitor_index {
+                    other_monitor.tagset[other_monitor.selected_tag_index] &= !added_tags;
+                }
+            }
+        }
+
         self.all_unminimize(arg)?;
 
         let monitor = &mut self.monitors[monitor_index];
         monitor.tagset[monitor.selected_tag_index] = new_tag_set;
         monitor.update_bar(monitor.bar.is_selected_monitor);
+        if config().shared_workspaces {
+            self.sync_workspaces()?;
+        }
         self.refresh_focus()?;
         self.arrange()?;
         self.refresh_bar()?;
@@ -1812,7 +2902,7 @@ impl DwmrApp {
             return Ok(());
         }
 
-        let selected_tag = arg.as_ref().unwrap().ui & TAGMASK;
+        let selected_tag = arg.as_ref().unwrap().ui & tagmask();
         if selected_tag == 0 {
             return Ok(());
         }
@@ -1843,7 +2933,7 @@ impl DwmrApp {
             return Ok(());
         }
 
-        let selected_tag = arg.as_ref().unwrap().ui & TAGMASK;
+        let selected_tag = arg.as_ref().unwrap().ui & tagmask();
         let new_tags = monitor.clients[selected_client_index.unwrap()].tags ^ selected_tag;
         if new_tags == 0 {
             return Ok(());
@@ -1862,7 +2952,7 @@ impl DwmrApp {
             return Ok(());
         }
 
-        let selected_tag = arg.as_ref().unwrap().ui & TAGMASK;
+        let selected_tag = arg.as_ref().unwrap().ui & tagmask();
         if selected_tag == 0 {
             return Ok(());
         }
@@ -1883,6 +2973,226 @@ impl DwmrApp {
         Ok(())
     }
 
+    pub unsafe fn swap_tags(&mut self, arg: &Option<Arg>) -> Result<()> {
+        if arg.is_none() {
+            return Ok(());
+        }
+
+        let target_tag = arg.as_ref().unwrap().ui & tagmask();
+        let monitor_index = self.selected_monitor_index.unwrap();
+        let monitor = &self.monitors[monitor_index];
+        let current_tag = monitor.tagset[monitor.selected_tag_index];
+        if target_tag.count_ones() != 1 || current_tag.count_ones() != 1 || target_tag == current_tag {
+            return Ok(());
+        }
+
+        for monitor in self.monitors.iter_mut() {
+            if !config().shared_workspaces && monitor.index != monitor_index {
+                continue;
+            }
+
+            for client in monitor.clients.iter_mut() {
+                let has_current_tag = (client.tags & current_tag) != 0;
+                let has_target_tag = (client.tags & target_tag) != 0;
+                if has_current_tag != has_target_tag {
+                    client.tags ^= current_tag | target_tag;
+                }
+            }
+        }
+
+        self.refresh_focus()?;
+        self.arrange()?;
+        self.refresh_bar()?;
+        Ok(())
+    }
+
+    pub unsafe fn merge_tags(&mut self, arg: &Option<Arg>) -> Result<()> {
+        if arg.is_none() {
+            return Ok(());
+        }
+
+        let target_tag = arg.as_ref().unwrap().ui & tagmask();
+        let monitor_index = self.selected_monitor_index.unwrap();
+        let monitor = &self.monitors[monitor_index];
+        let current_tag = monitor.tagset[monitor.selected_tag_index];
+        if target_tag == 0 || current_tag.count_ones() != 1 || (target_tag & current_tag) != 0 {
+            return Ok(());
+        }
+
+        for monitor in self.monitors.iter_mut() {
+            if !config().shared_workspaces && monitor.index != monitor_index {
+                continue;
+            }
+
+            for client in monitor.clients.iter_mut() {
+                if (client.tags & target_tag) != 0 {
+                    client.tags = (client.tags & !target_tag) | current_tag;
+                }
+            }
+        }
+
+        self.refresh_focus()?;
+        self.arrange()?;
+        self.refresh_bar()?;
+        Ok(())
+    }
+
+    pub unsafe fn spawn(&mut self, arg: &Option<Arg>) -> Result<()> {
+        if arg.is_none() {
+            return Ok(());
+        }
+
+        let config = config();
+        if let Some(command) = config.spawns.get(arg.unwrap().ui as usize) {
+            self.spawn_command(command);
+        }
+        Ok(())
+    }
+
+    fn spawn_command(&mut self, command: &SpawnCommand) {
+        match command.spawn() {
+            Ok(process_id) if command.has_placement() => self.pending_spawns.push(PendingSpawn::new(command, process_id)),
+            Ok(_) => {}
+            Err(e) => println!("Error: failed to spawn {command} - {e}"),
+        }
+    }
+
+    // Runs the autostart commands, or only those marked `on_reload` after a reload. Call it once the
+    // existing windows are scanned and arranged, so the guards see them.
+    pub unsafe fn autostart(&mut self, is_reload: bool) {
+        let config = config();
+        let processes = match config.autostart.iter().any(|autostart| autostart.check_process) {
+            true => snapshot_processes().unwrap_or_default(),
+            false => HashMap::new(),
+        };
+
+        for autostart in config.autostart.iter().filter(|autostart| !is_reload || autostart.on_reload) {
+            let is_managed = self.monitors.iter()
+                .flat_map(|monitor| monitor.clients.iter())
+                .any(|client| autostart.guard.is_match(client));
+            let is_running = autostart.check_process && processes.values()
+                .any(|process| process.exe_name.eq_ignore_ascii_case(&autostart.command.exe_name()));
+            if is_managed || is_running {
+                continue;
+            }
+
+            self.spawn_command(&autostart.command);
+        }
+    }
+
+    // Each step goes through the same dispatch as a key binding, skipping steps whose condition isn't met.
+    pub unsafe fn run_macro(&mut self, arg: &Option<Arg>) -> Result<()> {
+        if arg.is_none() {
+            return Ok(());
+        }
+
+        let config = config();
+        let Some(macro_entry) = config.macros.get(arg.unwrap().ui as usize) else {
+            return Ok(());
+        };
+
+        for step in macro_entry.steps.iter() {
+            self.sanitize_monitors();
+            let Some(monitor) = self.selected_monitor_index.and_then(|index| self.monitors.get(index)) else {
+                continue;
+            };
+            if !step.condition.is_met(&monitor.layout, monitor.visible_clinets_count() as usize) {
+                continue;
+            }
+
+            (step.func)(self, &step.arg)?;
+        }
+        Ok(())
+    }
+
+    // Swaps the regular bindings for the keys of the mode until Escape, the timeout or `exit_mode`.
+    pub unsafe fn enter_mode(&mut self, arg: &Option<Arg>) -> Result<()> {
+        if self.hwnd.0 == 0 || arg.is_none() {
+            return Ok(());
+        }
+
+        let config = config();
+        let mode_index = arg.unwrap().ui as usize;
+        let Some(mode) = config.modes.get(mode_index) else {
+            return Ok(());
+        };
+
+        match self.mode.and_then(|mode_index| config.modes.get(mode_index)) {
+            Some(current_mode) => self.ungrab_mode_keys(current_mode),
+            None => self.ungrab_keys(),
+        }
+        self.mode = Some(mode_index);
+        self.grab_mode_keys(mode);
+
+        let _ = KillTimer(self.hwnd, MODE_TIMER_ID);
+        if mode.timeout != 0 {
+            SetTimer(self.hwnd, MODE_TIMER_ID, mode.timeout, None);
+        }
+        self.refresh_bar()
+    }
+
+    pub unsafe fn exit_mode(&mut self, _arg: &Option<Arg>) -> Result<()> {
+        let Some(mode_index) = self.mode.take() else {
+            return Ok(());
+        };
+
+        let _ = KillTimer(self.hwnd, MODE_TIMER_ID);
+        if let Some(mode) = config().modes.get(mode_index) {
+            self.ungrab_mode_keys(mode);
+        }
+        self.grab_keys();
+        self.refresh_bar()
+    }
+
+    fn current_theme(&self) -> Theme {
+        let config = config();
+        config.themes[self.theme_index.unwrap_or(config.theme)].clone()
+    }
+
+    unsafe fn select_theme(&mut self, theme_index: usize) -> Result<()> {
+        let Some(theme) = config().themes.get(theme_index).cloned() else {
+            return Ok(());
+        };
+
+        self.theme_index = Some(theme_index);
+        for monitor in self.monitors.iter_mut() {
+            monitor.bar.theme = theme.clone();
+            if monitor.bar.hwnd.0 != 0 {
+                monitor.bar.apply_style()?;
+            }
+        }
+        self.refresh_bar()
+    }
+
+    // Lasts until the next reload, which goes back to `theme` of the configuration.
+    pub unsafe fn set_theme(&mut self, arg: &Option<Arg>) -> Result<()> {
+        if arg.is_none() {
+            return Ok(());
+        }
+
+        self.select_theme(arg.unwrap().ui as usize)
+    }
+
+    pub unsafe fn cycle_theme(&mut self, arg: &Option<Arg>) -> Result<()> {
+        if arg.is_none() {
+            return Ok(());
+        }
+
+        let config = config();
+        let theme_index = self.theme_index.unwrap_or(config.theme) as i32;
+        self.select_theme((theme_index + arg.unwrap().i).rem_euclid(config.themes.len() as i32) as usize)
+    }
+
+    // Suspends every other binding until it's pressed again.
+    pub unsafe fn toggle_passthrough(&mut self, _: &Option<Arg>) -> Result<()> {
+        if self.hwnd.0 == 0 {
+            return Ok(());
+        }
+
+        self.is_passthrough_toggled = !self.is_passthrough_toggled;
+        self.regrab_keys();
+        self.refresh_bar()
+    }
 
     pub unsafe fn quit(&mut self, _: &Option<Arg>) -> Result<()> {
         if self.hwnd.0 == 0 {
@@ -1998,6 +3308,18 @@ impl DwmrApp {
         Ok(())
     }
 
+    unsafe fn restore_focus(&self) -> Result<()> {
+        let selected_hwnd = match self.selected_monitor_index {
+            Some(selected_monitor_index) => self.monitors[selected_monitor_index].selected_hwnd,
+            None => HWND(0)
+        };
+
+        if selected_hwnd.0 == 0 {
+            return Self::unfocus();
+        }
+        Self::focus(&selected_hwnd)
+    }
+
     unsafe fn refresh_focus(&self) -> Result<()> {
         let selected_monitor = &self.monitors[self.selected_monitor_index.unwrap()];
         if selected_monitor.clients.len() == 0 {
@@ -2058,6 +3380,37 @@ impl DwmrApp {
         Ok(())
     }
 
+    pub unsafe fn focus_urgent(&mut self, _arg: &Option<Arg>) -> Result<()> {
+        let urgent_client = self.monitors.iter()
+            .flat_map(|monitor| monitor.clients.iter().rev())
+            .find(|client| client.is_urgent)
+            .cloned();
+
+        if urgent_client.is_none() {
+            return Ok(());
+        }
+
+        let urgent_client = urgent_client.unwrap();
+        self.selected_monitor_index = Some(urgent_client.monitor);
+        let monitor = &self.monitors[urgent_client.monitor];
+        if !Monitor::is_visible(&urgent_client, monitor.tagset[monitor.selected_tag_index]) {
+            self.view(&Some(Arg{ui: urgent_client.tags}))?;
+        }
+
+        for monitor in self.monitors.iter_mut() {
+            if monitor.find_client_index(&urgent_client.hwnd).is_some() {
+                self.selected_monitor_index = Some(monitor.index);
+                monitor.selected_hwnd = urgent_client.hwnd;
+                break;
+            }
+        }
+
+        self.set_focus(urgent_client.hwnd);
+        Self::focus(&urgent_client.hwnd)?;
+        self.refresh_bar()?;
+        Ok(())
+    }
+
     pub unsafe fn set_layout(&mut self, arg: &Option<Arg>) -> Result<()> {
         if arg.is_none() {
             return Ok(());
@@ -2084,6 +3437,66 @@ impl DwmrApp {
         Ok(())
     }
 
+    pub unsafe fn generate_rule(&mut self, _arg: &Option<Arg>) -> Result<()> {
+        let selected_monitor = &self.monitors[self.selected_monitor_index.unwrap()];
+        let selected_index = selected_monitor.get_selected_client_index();
+        if selected_index.is_none() {
+            return Ok(());
+        }
+
+        let selected_client = &selected_monitor.clients[selected_index.unwrap()];
+        let rule = Rule::from_client(selected_client).to_config_string();
+
+        if let Some(path) = &config().generated_rule_file {
+            let result = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| std::io::Write::write_all(&mut file, format!("{rule}\n").as_bytes()));
+            if let Err(e) = result {
+                println!("Error: failed to write rule to {path} - {e}");
+            }
+        }
+
+        if let Err(e) = Self::set_clipboard_text(&self.hwnd, &rule) {
+            println!("Error: failed to copy rule to clipboard - {e}");
+        }
+        Ok(())
+    }
+
+    unsafe fn set_clipboard_text(hwnd: &HWND, text: &str) -> Result<()> {
+        let text: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+        let size = text.len() * size_of::<u16>();
+
+        OpenClipboard(*hwnd)?;
+        let result = (|| {
+            EmptyClipboard()?;
+            let hglobal = GlobalAlloc(GMEM_MOVEABLE, size)?;
+            let buffer = GlobalLock(hglobal) as *mut u16;
+            if buffer.is_null() {
+                let error = Error::from_win32();
+                let _ = GlobalFree(hglobal);
+                return Err(error);
+            }
+            std::ptr::copy_nonoverlapping(text.as_ptr(), buffer, text.len());
+            let _ = GlobalUnlock(hglobal);
+
+            // The clipboard owns the memory once SetClipboardData succeeds.
+            if let Err(e) = SetClipboardData(CF_UNICODETEXT.0 as u32, HANDLE(hglobal.0 as isize)) {
+                let _ = GlobalFree(hglobal);
+                return Err(e);
+            }
+            Ok(())
+        })();
+        CloseClipboard()?;
+        result
+    }
+
+    pub unsafe fn reload(&mut self, _arg: &Option<Arg>) -> Result<()> {
+        let _ = self.reload_config();
+        Ok(())
+    }
+
     pub unsafe fn force_reset (&mut self, _arg: &Option<Arg>) -> Result<()> {
         SendMessageW(self.hwnd, WM_UPDATE_DISPLAY, WPARAM::default(), LPARAM::default());
         Ok(())
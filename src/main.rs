@@ -4,19 +4,116 @@ use windows::{
     core::*,
     Win32::{
         Foundation::*,
-        System::LibraryLoader::*,
+        System::{
+            Console::*,
+            LibraryLoader::*,
+        },
     }
 };
 
 fn main() -> Result<()> {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let config_path = match args.iter().position(|arg| arg == "--config") {
+        Some(index) if index + 1 < args.len() => {
+            let path = args.remove(index + 1);
+            args.remove(index);
+            Some(std::path::PathBuf::from(path))
+        }
+        Some(_) => usage(),
+        None => None,
+    };
+
     unsafe{
+        if !args.is_empty() {
+            // The GUI subsystem has no console of its own, so borrow the one we were started from.
+            let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+        }
+
+        let is_check = args.first().is_some_and(|command| command == "check-config");
+        if let Err(e) = config_file::init_config(config_path) {
+            // check-config reports every problem on its own.
+            if !is_check {
+                println!("Error: failed to load configuration, using defaults - {e}");
+            }
+        }
+
+        if let Some(command) = args.first() {
+            return run_command(command, &args[1..]);
+        }
+
         let hmodule = GetModuleHandleW(None)?;
         let hinstance: HINSTANCE = hmodule.into();
         let mut app = DwmrApp::default();
         app.setup(&hinstance)?;
         app.scan()?;
         app.arrange()?;
+        app.autostart(false);
         DwmrApp::run()?;
     }
     Ok(())
 }
+
+unsafe fn run_command(command: &str, args: &[String]) -> Result<()> {
+    match command {
+        "inspect" => {
+            let reports = inspector::inspect_windows()?;
+            if args.iter().any(|arg| arg == "--json") {
+                println!("{}", inspector::format_json(&reports));
+            } else {
+                print!("{}", inspector::format_table(&reports));
+            }
+        }
+        "reload" => {
+            if !send_reload()? {
+                // The running instance keeps its old configuration; show why the new one was rejected.
+                if let Some(Err(e)) = config_file::config_path().map(|path| config_file::Config::load(&path)) {
+                    eprintln!("Error: {e}");
+                } else {
+                    eprintln!("Error: failed to reload configuration");
+                }
+                std::process::exit(1);
+            }
+            println!("Configuration reloaded");
+        }
+        "check-config" => check_config(),
+        _ => {
+            eprintln!("Unknown command: {command}");
+            usage();
+        }
+    }
+    Ok(())
+}
+
+// Exits with 1 when the file has any error or warning, so it can run in CI.
+fn check_config() {
+    let Some(path) = config_file::config_path() else {
+        eprintln!("Error: no configuration path, pass one with --config <path>");
+        std::process::exit(1);
+    };
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("{}: error: failed to read - {e}", path.display());
+            std::process::exit(1);
+        }
+    };
+
+    let (_, diagnostics) = config_file::Config::check(&text);
+    for diagnostic in diagnostics.iter() {
+        let severity = if diagnostic.is_warning() { "warning" } else { "error" };
+        match diagnostic.line() {
+            Some(line) => println!("{}:{line}: {severity}: {}", path.display(), diagnostic.message()),
+            None => println!("{}: {severity}: {}", path.display(), diagnostic.message()),
+        }
+    }
+
+    if !diagnostics.is_empty() {
+        std::process::exit(1);
+    }
+    println!("{}: ok", path.display());
+}
+
+fn usage() -> ! {
+    eprintln!("Usage: dwmr-win32 [--config <path>] [inspect [--json] | reload | check-config]");
+    std::process::exit(2);
+}
@@ -0,0 +1,147 @@
+use core::fmt;
+use regex::{Regex, RegexBuilder};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    Substring,
+    Exact,
+    Glob,
+    Regex,
+}
+
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    kind: MatchKind,
+    pattern: String,
+    ignore_case: bool,
+    negate: bool,
+    regex: Option<Regex>,
+}
+
+impl Matcher {
+    pub fn new(kind: MatchKind, pattern: &str, ignore_case: bool, negate: bool) -> Result<Matcher, regex::Error> {
+        let regex = match kind {
+            MatchKind::Substring | MatchKind::Exact => None,
+            MatchKind::Glob => Some(Self::compile(&Self::glob_to_regex(pattern), ignore_case)?),
+            MatchKind::Regex => Some(Self::compile(pattern, ignore_case)?),
+        };
+
+        Ok(Matcher {
+            kind,
+            pattern: pattern.to_string(),
+            ignore_case,
+            negate,
+            regex,
+        })
+    }
+
+    pub fn substring(pattern: &str) -> Matcher {
+        Self::new(MatchKind::Substring, pattern, false, false).unwrap()
+    }
+
+    pub fn exact(pattern: &str) -> Matcher {
+        Self::new(MatchKind::Exact, pattern, false, false).unwrap()
+    }
+
+    pub fn glob(pattern: &str) -> Matcher {
+        Self::new(MatchKind::Glob, pattern, false, false).unwrap()
+    }
+
+    pub fn regex(pattern: &str) -> Result<Matcher, regex::Error> {
+        Self::new(MatchKind::Regex, pattern, false, false)
+    }
+
+    pub fn ignore_case(self) -> Matcher {
+        Self::new(self.kind, &self.pattern, true, self.negate).unwrap_or(self)
+    }
+
+    pub fn negate(mut self) -> Matcher {
+        self.negate = !self.negate;
+        self
+    }
+
+    pub fn kind(&self) -> MatchKind {
+        self.kind
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn is_ignore_case(&self) -> bool {
+        self.ignore_case
+    }
+
+    pub fn is_negated(&self) -> bool {
+        self.negate
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        let is_match = match (self.kind, self.ignore_case) {
+            (MatchKind::Substring, false) => text.contains(&self.pattern),
+            (MatchKind::Substring, true) => text.to_lowercase().contains(&self.pattern.to_lowercase()),
+            (MatchKind::Exact, false) => text == self.pattern,
+            (MatchKind::Exact, true) => text.to_lowercase() == self.pattern.to_lowercase(),
+            (MatchKind::Glob | MatchKind::Regex, _) => self.regex.as_ref().is_some_and(|regex| regex.is_match(text)),
+        };
+        is_match != self.negate
+    }
+
+    // A plain string for case-sensitive substrings, an inline table otherwise.
+    pub fn to_config_string(&self) -> String {
+        let pattern = toml::Value::from(self.pattern.as_str());
+        if self.kind == MatchKind::Substring && !self.ignore_case && !self.negate {
+            return pattern.to_string();
+        }
+
+        let mut table = format!("{{ {} = {}", self.kind, pattern);
+        if self.ignore_case {
+            table += ", ignore_case = true";
+        }
+        if self.negate {
+            table += ", negate = true";
+        }
+        table + " }"
+    }
+
+    fn compile(pattern: &str, ignore_case: bool) -> Result<Regex, regex::Error> {
+        RegexBuilder::new(pattern)
+            .case_insensitive(ignore_case)
+            .build()
+    }
+
+    fn glob_to_regex(pattern: &str) -> String {
+        let mut regex = String::from("^");
+        for c in pattern.chars() {
+            match c {
+                '*' => regex.push_str(".*"),
+                '?' => regex.push('.'),
+                _ => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+        regex
+    }
+}
+
+impl fmt::Display for MatchKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            MatchKind::Substring => "substring",
+            MatchKind::Exact => "exact",
+            MatchKind::Glob => "glob",
+            MatchKind::Regex => "regex",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl fmt::Display for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}:{:?}", if self.negate { "!" } else { "" }, self.kind, self.pattern)?;
+        if self.ignore_case {
+            write!(f, " (ignore case)")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,90 @@
+use windows::{
+    core::*,
+    Win32::{
+        Foundation::*,
+        System::{
+            Diagnostics::ToolHelp::*,
+            Threading::*,
+        },
+        UI::WindowsAndMessaging::*,
+    }
+};
+
+use std::{
+    collections::HashMap,
+    mem::size_of,
+};
+
+#[derive(Default, Clone, Debug)]
+pub struct ProcessEntry {
+    pub process_id: u32,
+    pub parent_process_id: u32,
+    pub exe_name: String,
+}
+
+pub unsafe fn get_process_id(hwnd: &HWND) -> Result<u32> {
+    let mut process_id: u32 = 0;
+    if GetWindowThreadProcessId(*hwnd, Some(&mut process_id as *mut _)) == 0 {
+        GetLastError()?;
+    }
+    Ok(process_id)
+}
+
+pub unsafe fn get_process_path(process_id: u32) -> Result<String> {
+    let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, process_id)?;
+
+    let mut path_buf = [0u16; MAX_PATH as usize];
+    let mut path_length = path_buf.len() as u32;
+    let result = QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, PWSTR::from_raw(path_buf.as_mut_ptr()), &mut path_length);
+    CloseHandle(handle)?;
+    result?;
+
+    Ok(String::from_utf16_lossy(&path_buf[..path_length as usize]))
+}
+
+pub fn file_basename(path: &str) -> &str {
+    path.rsplit(['\\', '/']).next().unwrap_or(path)
+}
+
+pub unsafe fn snapshot_processes() -> Result<HashMap<u32, ProcessEntry>> {
+    let snapshot = CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0)?;
+
+    let mut processes = HashMap::new();
+    let mut entry = PROCESSENTRY32W {
+        dwSize: size_of::<PROCESSENTRY32W>() as u32,
+        ..Default::default()
+    };
+
+    let mut result = Process32FirstW(snapshot, &mut entry);
+    while result.is_ok() {
+        let exe_name = PCWSTR::from_raw(entry.szExeFile.as_ptr()).to_string().unwrap_or_default();
+        processes.insert(entry.th32ProcessID, ProcessEntry {
+            process_id: entry.th32ProcessID,
+            parent_process_id: entry.th32ParentProcessID,
+            exe_name,
+        });
+        result = Process32NextW(snapshot, &mut entry);
+    }
+
+    CloseHandle(snapshot)?;
+    Ok(processes)
+}
+
+pub fn get_parent_process(processes: &HashMap<u32, ProcessEntry>, process_id: u32) -> Option<&ProcessEntry> {
+    let parent_process_id = processes.get(&process_id)?.parent_process_id;
+    processes.get(&parent_process_id)
+}
+
+// Nearest ancestor first. Parent ids can be stale after process id reuse, so a cycle ends the walk.
+pub fn get_ancestor_process_ids(processes: &HashMap<u32, ProcessEntry>, process_id: u32) -> Vec<u32> {
+    let mut ancestors: Vec<u32> = Vec::new();
+    let mut current = process_id;
+    while let Some(parent) = get_parent_process(processes, current) {
+        if parent.process_id == 0 || parent.process_id == process_id || ancestors.contains(&parent.process_id) {
+            break;
+        }
+        ancestors.push(parent.process_id);
+        current = parent.process_id;
+    }
+    ancestors
+}
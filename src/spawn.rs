@@ -0,0 +1,94 @@
+use std::{
+    process::Command,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    process_utils::file_basename,
+    MonitorTarget,
+    Rule,
+};
+
+// How long a spawned program has to open its window before the placement is dropped.
+pub const PLACEMENT_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Default)]
+pub struct SpawnCommand {
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    pub env: Vec<(String, String)>,
+    pub tags: Option<u32>,
+    pub monitor: Option<MonitorTarget>,
+}
+
+impl SpawnCommand {
+    // Returns the process id of the new process.
+    pub fn spawn(&self) -> std::io::Result<u32> {
+        let mut command = Command::new(&self.program);
+        command.args(&self.args);
+        command.envs(self.env.iter().map(|(key, value)| (key, value)));
+        if let Some(cwd) = &self.cwd {
+            command.current_dir(cwd);
+        }
+        Ok(command.spawn()?.id())
+    }
+
+    pub fn has_placement(&self) -> bool {
+        self.tags.is_some() || self.monitor.is_some()
+    }
+
+    // C:\Tools\slack runs slack.exe.
+    pub fn exe_name(&self) -> String {
+        let name = file_basename(&self.program);
+        match name.contains('.') {
+            true => name.to_string(),
+            false => format!("{name}.exe"),
+        }
+    }
+}
+
+// A command run once at startup, after the existing windows are managed.
+#[derive(Debug, Clone)]
+pub struct Autostart {
+    pub command: SpawnCommand,
+    pub on_reload: bool,
+    // Skipped while a managed window matches the guard.
+    pub guard: Rule,
+    // Without an explicit guard, a running process of the same executable also skips it, since tray apps often have no window.
+    pub check_process: bool,
+}
+
+impl std::fmt::Display for SpawnCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.program)?;
+        for arg in self.args.iter() {
+            write!(f, " {arg}")?;
+        }
+        Ok(())
+    }
+}
+
+// Where the first window of a spawned process goes.
+#[derive(Debug, Clone)]
+pub struct PendingSpawn {
+    pub process_id: u32,
+    pub tags: Option<u32>,
+    pub monitor: Option<MonitorTarget>,
+    pub expires: Instant,
+}
+
+impl PendingSpawn {
+    pub fn new(command: &SpawnCommand, process_id: u32) -> PendingSpawn {
+        PendingSpawn {
+            process_id,
+            tags: command.tags,
+            monitor: command.monitor.clone(),
+            expires: Instant::now() + PLACEMENT_TIMEOUT,
+        }
+    }
+
+    pub fn is_expired(&self) -> bool {
+        Instant::now() >= self.expires
+    }
+}
@@ -1,7 +1,778 @@
 #[cfg(test)]
 mod tests {
+    use crate::*;
+
     #[test]
     fn test() {
         assert_eq!(1, 1);
     }
+
+    #[test]
+    fn matcher_kinds() {
+        assert!(Matcher::substring("Fire").is_match("Firefox"));
+        assert!(!Matcher::substring("fire").is_match("Firefox"));
+        assert!(Matcher::substring("fire").ignore_case().is_match("Firefox"));
+
+        assert!(Matcher::exact("Notepad").is_match("Notepad"));
+        assert!(!Matcher::exact("Notepad").is_match("Notepad++"));
+        assert!(Matcher::exact("notepad").ignore_case().is_match("NOTEPAD"));
+
+        assert!(Matcher::glob("*\\firefox.exe").is_match("\\Device\\HarddiskVolume3\\Mozilla Firefox\\firefox.exe"));
+        assert!(Matcher::glob("Picture?in?Picture").is_match("Picture-in-Picture"));
+        assert!(!Matcher::glob("Picture*").is_match("A Picture"));
+        assert!(Matcher::glob("*.EXE").ignore_case().is_match("app.exe"));
+
+        let regex = Matcher::regex("^(Picture-in-Picture|화면 속 화면)$").unwrap();
+        assert!(regex.is_match("화면 속 화면"));
+        assert!(!regex.is_match("picture-in-picture"));
+        assert!(regex.ignore_case().is_match("picture-in-picture"));
+    }
+
+    #[test]
+    fn matcher_negate() {
+        let matcher = Matcher::substring("Steam").negate();
+        assert!(matcher.is_match("Discord"));
+        assert!(!matcher.is_match("Steam"));
+        assert!(Matcher::regex("^a").unwrap().negate().ignore_case().is_match("bcd"));
+        assert!(!Matcher::regex("^a").unwrap().negate().ignore_case().is_match("Abc"));
+    }
+
+    #[test]
+    fn matcher_invalid_regex() {
+        assert!(Matcher::regex("(unclosed").is_err());
+        assert!(Matcher::new(MatchKind::Glob, "[*", false, false).is_ok());
+    }
+
+    #[test]
+    fn process_basename() {
+        assert_eq!(file_basename("C:\\Program Files\\Mozilla Firefox\\firefox.exe"), "firefox.exe");
+        assert_eq!(file_basename("C:/tools/app.exe"), "app.exe");
+        assert_eq!(file_basename("app.exe"), "app.exe");
+        assert_eq!(file_basename(""), "");
+    }
+
+    #[test]
+    fn rule_rect_resolve() {
+        let area = Rect{x: 1920, y: 20, width: 1000, height: 500};
+        let absolute = RuleRect::Absolute{x: 10, y: 20, width: 300, height: 200}.resolve(&area);
+        assert_eq!(absolute, Rect{x: 1930, y: 40, width: 300, height: 200});
+
+        let percent = RuleRect::Percent{x: 0.25, y: 0.1, width: 0.5, height: 0.8}.resolve(&area);
+        assert_eq!(percent, Rect{x: 2170, y: 70, width: 500, height: 400});
+
+        assert_eq!(percent.centered_in(&area), Rect{x: 2170, y: 70, width: 500, height: 400});
+        assert_eq!(absolute.centered_in(&area), Rect{x: 2270, y: 170, width: 300, height: 200});
+    }
+
+    #[test]
+    fn rule_resolve_merges_matching_rules() {
+        let client = Client{title: "Save As".to_string(), class: "#32770".to_string(), process_name: "slack.exe".to_string(), ..Default::default()};
+        let rules = [
+            Rule{class: Some(Matcher::exact("#32770")), is_floating: Some(true), ..Default::default()},
+            Rule{process_name: Some(Matcher::exact("slack.exe")), tags: Some(1 << 3), ..Default::default()},
+            Rule{process_name: Some(Matcher::exact("discord.exe")), tags: Some(1 << 5), ..Default::default()},
+        ];
+
+        let resolved = Rule::resolve(rules.iter(), &client).unwrap();
+        assert_eq!(resolved.is_floating, Some(true));
+        assert_eq!(resolved.tags, Some(1 << 3));
+        assert!(resolved.is_sticky.is_none());
+
+        let unmatched = Client{process_name: "notepad.exe".to_string(), ..Default::default()};
+        assert!(Rule::resolve(rules.iter(), &unmatched).is_none());
+    }
+
+    #[test]
+    fn rule_resolve_priority_and_stop() {
+        let client = Client{title: "Slack".to_string(), ..Default::default()};
+        let rules = [
+            Rule{title: Some(Matcher::substring("Slack")), tags: Some(1 << 1), priority: 10, ..Default::default()},
+            Rule{title: Some(Matcher::substring("Sl")), tags: Some(1 << 2), is_floating: Some(false), ..Default::default()},
+        ];
+        let resolved = Rule::resolve(rules.iter(), &client).unwrap();
+        assert_eq!(resolved.tags, Some(1 << 1));
+        assert_eq!(resolved.is_floating, Some(false));
+
+        let rules = [
+            Rule{title: Some(Matcher::substring("Slack")), tags: Some(1 << 1), stop: true, ..Default::default()},
+            Rule{title: Some(Matcher::substring("Sl")), tags: Some(1 << 2), is_floating: Some(true), ..Default::default()},
+        ];
+        let resolved = Rule::resolve(rules.iter(), &client).unwrap();
+        assert_eq!(resolved.tags, Some(1 << 1));
+        assert!(resolved.is_floating.is_none());
+
+        // A stop rule only skips the rules with a lower priority.
+        let rules = [
+            Rule{title: Some(Matcher::substring("Slack")), tags: Some(1 << 1), is_sticky: Some(true), stop: true, ..Default::default()},
+            Rule{title: Some(Matcher::substring("Sl")), tags: Some(1 << 2), is_floating: Some(true), priority: 10, ..Default::default()},
+            Rule{title: Some(Matcher::substring("ack")), no_focus: Some(true), priority: -10, ..Default::default()},
+        ];
+        let resolved = Rule::resolve(rules.iter(), &client).unwrap();
+        assert_eq!(resolved.tags, Some(1 << 2));
+        assert_eq!(resolved.is_floating, Some(true));
+        assert_eq!(resolved.is_sticky, Some(true));
+        assert!(resolved.no_focus.is_none());
+
+        // At the same priority, later rules override the fields they set.
+        let rules = [
+            Rule{title: Some(Matcher::substring("Slack")), tags: Some(1 << 1), is_sticky: Some(true), ..Default::default()},
+            Rule{title: Some(Matcher::substring("Sl")), tags: Some(1 << 2), ..Default::default()},
+        ];
+        let resolved = Rule::resolve(rules.iter(), &client).unwrap();
+        assert_eq!(resolved.tags, Some(1 << 2));
+        assert_eq!(resolved.is_sticky, Some(true));
+    }
+
+    #[test]
+    fn client_inherit_owner() {
+        let owner = Client{hwnd: HWND(1), tags: 1 << 3, monitor: 1, ..Default::default()};
+        let dialog = Client{class: "#32770".to_string(), tags: 1, ..Default::default()};
+        let config = Config::default();
+
+        let mut client = dialog.clone();
+        client.inherit_owner(None, &config);
+        assert!(client.is_floating);
+        assert_eq!(client.tags, 1);
+
+        let mut client = Client{class: "Notepad".to_string(), ..Default::default()};
+        client.inherit_owner(Some(&owner), &config);
+        assert!(client.is_floating);
+        assert_eq!((client.owner, client.tags, client.monitor), (HWND(1), 1 << 3, 1));
+
+        let config = Config{float_owned_windows: false, ..Config::default()};
+        let mut client = dialog.clone();
+        client.inherit_owner(Some(&owner), &config);
+        assert!(!client.is_floating);
+        assert_eq!((client.owner, client.tags, client.monitor), (HWND(0), 1, 0));
+    }
+
+    #[test]
+    fn monitor_attach() {
+        let attached = |attach: Attach, master_count: u32, selected_hwnd: isize| -> Vec<isize> {
+            let mut monitor = Monitor{
+                master_count,
+                selected_hwnd: HWND(selected_hwnd),
+                clients: (1..=4).map(|hwnd| Client{hwnd: HWND(hwnd), ..Default::default()}).collect(),
+                ..Default::default()
+            };
+            monitor.attach(Client{hwnd: HWND(9), ..Default::default()}, attach);
+            monitor.clients.iter().map(|client| client.hwnd.0).collect()
+        };
+
+        // The master is the last client, and HWND(2) is focused.
+        assert_eq!(attached(Attach::Master, 1, 2), [1, 2, 3, 4, 9]);
+        assert_eq!(attached(Attach::Aside, 1, 2), [1, 2, 3, 9, 4]);
+        assert_eq!(attached(Attach::Aside, 2, 2), [1, 2, 9, 3, 4]);
+        assert_eq!(attached(Attach::Below, 1, 2), [1, 9, 2, 3, 4]);
+        assert_eq!(attached(Attach::Above, 1, 2), [1, 2, 9, 3, 4]);
+        assert_eq!(attached(Attach::Bottom, 1, 2), [9, 1, 2, 3, 4]);
+        assert_eq!(attached(Attach::Below, 1, 0), [1, 2, 3, 4, 9]);
+        assert_eq!(attached(Attach::Above, 1, 0), [1, 2, 3, 4, 9]);
+    }
+
+    #[test]
+    fn exclude_rule_match() {
+        let window = WindowProperties{title: "Search".to_string(), class: "Windows.UI.Core.CoreWindow".to_string(), process_name: "SearchHost.exe".to_string(), style: 0x1000_0000, exstyle: 0x80};
+        assert!(ExcludeRule{title: Some(Matcher::exact("Search")), ..Default::default()}.is_match(&window));
+        assert!(ExcludeRule{process_name: Some(Matcher::exact("searchhost.exe").ignore_case()), exstyle: 0x80, ..Default::default()}.is_match(&window));
+        assert!(!ExcludeRule{class: Some(Matcher::exact("Progman")), ..Default::default()}.is_match(&window));
+        assert!(!ExcludeRule{title: Some(Matcher::exact("Search")), style: 0x0800_0000, ..Default::default()}.is_match(&window));
+    }
+
+    #[test]
+    fn inspector_output() {
+        let reports = [crate::inspector::WindowReport{
+            hwnd: 1234,
+            title: "A very long window title that does not fit in the column".to_string(),
+            class: "#32770".to_string(),
+            process_name: "notepad.exe".to_string(),
+            decision: ManageDecision::Cloaked.to_string(),
+            rules: vec![0, 2],
+            ..Default::default()
+        }];
+
+        let table = crate::inspector::format_table(&reports);
+        assert_eq!(table.lines().count(), 2);
+        assert!(table.contains("A very long window title that d…"));
+        assert!(table.contains("0,2"));
+        assert!(table.contains("ignored: cloaked"));
+
+        let json: serde_json::Value = serde_json::from_str(&crate::inspector::format_json(&reports)).unwrap();
+        assert_eq!(json[0]["hwnd"], 1234);
+        assert_eq!(json[0]["class"], "#32770");
+        assert_eq!(json[0]["rules"][1], 2);
+    }
+
+    #[test]
+    fn rule_from_client() {
+        let client = Client{
+            title: "Picture-in-Picture".to_string(),
+            class: "MozillaDialogClass".to_string(),
+            process_name: "firefox.exe".to_string(),
+            is_floating: true,
+            tags: 1 << 3,
+            monitor: 1,
+            ..Default::default()
+        };
+
+        let rule = Rule::from_client(&client);
+        assert!(rule.is_match(&client));
+        assert_eq!(
+            rule.to_config_string(),
+            "[[rules]]\n\
+            title = { exact = \"Picture-in-Picture\" }\n\
+            class = { exact = \"MozillaDialogClass\" }\n\
+            process_name = { exact = \"firefox.exe\", ignore_case = true }\n\
+            is_floating = true\n\
+            tags = [4]\n\
+            monitor = 1\n");
+
+        let parsed = Config::parse(&rule.to_config_string()).unwrap();
+        assert_eq!(parsed.rules.len(), 1);
+        assert!(parsed.rules[0].is_match(&client));
+        assert_eq!(parsed.rules[0].to_config_string(), rule.to_config_string());
+
+        let untitled = Client{tags: 0b101, ..Default::default()};
+        assert_eq!(Rule::from_client(&untitled).to_config_string(), "[[rules]]\nis_floating = false\ntags = [1, 3]\nmonitor = 0\n");
+        assert_eq!(Matcher::regex("^a$").unwrap().ignore_case().negate().to_config_string(), "{ regex = \"^a$\", ignore_case = true, negate = true }");
+        assert_eq!(Matcher::substring("a \"b\"").to_config_string(), "\"a \\\"b\\\"\"");
+    }
+
+    #[test]
+    fn process_ancestors() {
+        let processes: HashMap<u32, ProcessEntry> = [(1, 0, "wininit.exe"), (10, 1, "WindowsTerminal.exe"), (20, 10, "pwsh.exe"), (30, 20, "mpv.exe"), (40, 50, "a.exe"), (50, 40, "b.exe")]
+            .into_iter()
+            .map(|(process_id, parent_process_id, exe_name)| (process_id, ProcessEntry{process_id, parent_process_id, exe_name: exe_name.to_string()}))
+            .collect();
+
+        assert_eq!(get_ancestor_process_ids(&processes, 30), vec![20, 10, 1]);
+        assert_eq!(get_ancestor_process_ids(&processes, 1), Vec::<u32>::new());
+        assert_eq!(get_ancestor_process_ids(&processes, 40), vec![50]);
+        assert_eq!(get_ancestor_process_ids(&processes, 99), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn reload_mask_tags() {
+        assert_eq!(mask_tags(0b1010, 0b111, 1), 0b010);
+        assert_eq!(mask_tags(0b1000, 0b111, 0b100), 0b100);
+    }
+
+    #[test]
+    fn config_defaults() {
+        let config = Config::default();
+        assert_eq!(config.tags.len(), TAGS.len());
+        assert_eq!(config.tagmask(), 0b1_1111_1111);
+        assert_eq!(config.keys.len(), KEYS.len() + TAG_KEYS.len() * TAG_KEYS[0].len());
+        assert_eq!(config.rules.len(), RULES.len());
+        assert_eq!(Config::parse("").unwrap().keys.len(), config.keys.len());
+
+        let example = Config::parse(include_str!("../config.example.toml")).unwrap();
+        assert_eq!(example.keys.len(), config.keys.len());
+        assert_eq!(example.rules.len(), config.rules.len());
+        assert_eq!(example.themes.len(), config.themes.len());
+        assert_eq!(example.themes[0].selected.background.b, config.themes[0].selected.background.b);
+        for (example_key, key) in example.keys.iter().zip(config.keys.iter()) {
+            assert_eq!((example_key.mod_key, example_key.key), (key.mod_key, key.key));
+        }
+    }
+
+    #[test]
+    fn config_parse() {
+        let config = Config::parse(r##"
+            tags = ["web", "code", "chat"]
+            mod_key = ["Win"]
+            shared_workspaces = true
+            default_attach = "aside"
+            monitor_attach = [{ monitor = 1, attach = "bottom" }]
+
+            [bar]
+            font = "Consolas"
+            colors = { background = "#102030", urgent_box = "#ff000080" }
+
+            [[rules]]
+            class = { regex = "^Chrome_WidgetWin_\\d$", ignore_case = true }
+            process_name = "slack"
+            tags = [2, 3]
+            monitor = "DISPLAY2"
+            rect = { x = 0.1, y = 0.1, width = 0.8, height = 0.8, percent = true }
+
+            [[keys]]
+            modifiers = ["Mod", "Shift"]
+            key = "g"
+            action = "generate_rule"
+
+            [[keys]]
+            modifiers = ["Mod"]
+            key = "V"
+            action = "view"
+            arg = [1, 3]
+
+            [[tag_keys]]
+            modifiers = ["Mod"]
+            action = "view"
+        "##).unwrap();
+
+        assert_eq!(config.tags, vec!["web", "code", "chat"]);
+        assert_eq!(config.tagmask(), 0b111);
+        assert_eq!(config.mod_key, MOD_WIN);
+        assert!(config.shared_workspaces);
+        assert_eq!(config.default_attach, Attach::Aside);
+        assert_eq!(config.monitor_attach, vec![(1, Attach::Bottom)]);
+        assert_eq!(config.bar_font, "Consolas");
+        assert_eq!(config.themes[0].normal.background.g, 0x20 as f32 / 255.0);
+        assert_eq!(config.themes[0].urgent.background.a, 0x80 as f32 / 255.0);
+        assert_eq!(config.themes[0].selected.background.r, BAR_COLOR_SELECTED_BOX.r);
+
+        assert_eq!(config.rules.len(), 1);
+        let client = Client{class: "chrome_widgetwin_1".to_string(), process_name: "slack.exe".to_string(), ..Default::default()};
+        assert!(config.rules[0].is_match(&client));
+        assert_eq!(config.rules[0].tags, Some(0b110));
+
+        assert_eq!(config.keys.len(), 2 + 3);
+        assert_eq!(config.keys[0].mod_key, MOD_WIN | MOD_SHIFT);
+        assert_eq!(config.keys[0].key, VK_G);
+        assert_eq!(unsafe { config.keys[1].arg.unwrap().ui }, 0b101);
+        assert_eq!(config.keys[4].key, VK_3);
+        assert_eq!(unsafe { config.keys[4].arg.unwrap().ui }, 1 << 2);
+
+        let (remapped, diagnostics) = Config::check("mod_key = [\"Win\"]");
+        assert_eq!(remapped.keys[0].mod_key, MOD_WIN);
+        assert!(diagnostics.is_empty());
+
+        // With Ctrl, toggle_view and tag_all fall onto view, and toggle_tag onto tag.
+        let (_, diagnostics) = Config::check("mod_key = [\"Ctrl\"]");
+        assert_eq!(diagnostics.len(), 3 * 9);
+        assert!(diagnostics.iter().all(|diagnostic| diagnostic.is_warning() && diagnostic.line() == Some(1)));
+        assert_eq!(diagnostics[0].message(), "the default toggle_view binding on Ctrl+1 is replaced by the default view binding");
+        assert_eq!(diagnostics[1].message(), "the default toggle_tag binding on Ctrl+Shift+1 is replaced by the default tag binding");
+        assert_eq!(diagnostics[2].message(), "the default tag_all binding on Ctrl+1 is replaced by the default view binding");
+    }
+
+    #[test]
+    fn config_mod_key_remap() {
+        // Mod+1 becomes Ctrl+1, so view takes the key of the default tag_all binding.
+        let config = Config::parse("mod_key = [\"Ctrl\"]").unwrap();
+        let bound: Vec<Key> = config.keys.iter().filter(|key| key.spec() == KeySpec::new(MOD_CONTROL, VK_1)).cloned().collect();
+        assert_eq!(bound.len(), 1);
+        assert_eq!(unsafe { bound[0].arg.unwrap().ui }, 1 << 0);
+
+        // A binding of the user replaces the default tag key on the same key.
+        let config = Config::parse("[[keys]]\nkey = \"Mod+1\"\naction = \"zoom\"").unwrap();
+        let bound: Vec<&Key> = config.keys.iter().filter(|key| key.spec() == KeySpec::new(MOD_ALT, VK_1)).collect();
+        assert_eq!(bound.len(), 1);
+        assert!(bound[0].arg.is_none());
+        assert_eq!(config.keys.len(), 1 + TAG_KEYS.len() * TAG_KEYS[0].len() - 1);
+        let (_, diagnostics) = Config::check("[[keys]]\nkey = \"Mod+1\"\naction = \"zoom\"");
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].is_warning());
+        assert_eq!(diagnostics[0].line(), Some(1));
+        assert_eq!(diagnostics[0].message(), "the default view binding on Alt+1 is replaced by zoom on line 1");
+
+        // The user's own entries still collide.
+        assert!(Config::parse("[[keys]]\nkey = \"Mod+1\"\naction = \"zoom\"\n[[tag_keys]]\nmodifiers = [\"Mod\"]\naction = \"view\"").is_err());
+    }
+
+    #[test]
+    fn config_errors() {
+        let errors = [
+            "[[keys]]\nkey = \"Q\"\naction = \"explode\"",
+            "[[keys]]\nkey = \"Q\"\naction = \"focus_stack\"",
+            "[[keys]]\nkey = \"Q\"\naction = \"quit\"\narg = 1",
+            "[[keys]]\nmodifiers = [\"Hyper\"]\nkey = \"Q\"\naction = \"quit\"",
+            "[[tag_keys]]\naction = \"zoom\"",
+            "[bar.colors]\nbackground = \"#12345\"",
+            "[[rules]]\ntags = [10]",
+            "[[rules]]\ntitle = { regex = \"(unclosed\" }",
+            "[[rules]]\ntitle = { exact = \"a\", glob = \"b\" }",
+            "unknown_setting = true",
+            "[[exclude_rules]]\nname = \"everything\"",
+        ];
+        for text in errors {
+            assert!(Config::parse(text).is_err(), "{text}");
+        }
+
+        let (_, diagnostics) = Config::check("[[exclude_rules]]\nname = \"everything\"\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line(), Some(1));
+        assert!(diagnostics[0].message().contains("exclude_rules[0]"));
+    }
+
+    #[test]
+    fn config_check() {
+        let text = r##"
+[bar.colors]
+background = "#12345"
+
+[[rules]]
+class = "Chrome"
+stop = true
+
+[[rules]]
+class = "Chrome"
+title = "YouTube"
+is_floating = true
+
+[[rules]]
+title = { regex = "(" }
+
+[[rules]]
+tags = [10]
+
+[[keys]]
+modifiers = ["Alt"]
+key = "J"
+action = "focus_stack"
+arg = 1
+
+[[keys]]
+modifiers = ["Alt"]
+key = "J"
+action = "zoom"
+
+[[keys]]
+modifiers = ["Alt"]
+key = "K"
+action = "jump"
+
+[[rules]]
+class = "Chrome"
+title = "Meet"
+priority = 5
+is_floating = true
+"##;
+        let (_, diagnostics) = Config::check(text);
+        let lines: Vec<(Option<usize>, bool)> = diagnostics.iter().map(|e| (e.line(), e.is_warning())).collect();
+        assert_eq!(lines, vec![
+            (Some(3), false),
+            (Some(14), false),
+            (Some(17), false),
+            (Some(9), true),
+            (Some(31), false),
+            (Some(26), false),
+        ]);
+        assert!(diagnostics[4].message().contains("jump"));
+        assert!(diagnostics[5].message().contains("Alt+J"));
+        assert!(Config::parse(text).is_err());
+
+        let (_, diagnostics) = Config::check("tags = [\"a\"]\nunknown = 1\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line(), Some(2));
+
+        let (_, diagnostics) = Config::check("mod_key = [\"Ctrl\"]\n");
+        assert!(!diagnostics.is_empty() && diagnostics.iter().all(|diagnostic| diagnostic.is_warning()));
+
+        // The commented swap_tags and merge_tags bindings of the example add no collision for any mod_key.
+        let example = include_str!("../config.example.toml");
+        assert!(Config::check(example).1.is_empty());
+        let uncommented = example
+            .replace("# [[tag_keys]]\n# modifiers", "[[tag_keys]]\nmodifiers")
+            .replace("# action = \"swap_tags\"", "action = \"swap_tags\"")
+            .replace("# action = \"merge_tags\"", "action = \"merge_tags\"");
+        for mod_key in ["Alt", "Win", "Ctrl"] {
+            let with_mod_key = |text: &str| text.replace("mod_key = [\"Alt\"]", &format!("mod_key = [\"{mod_key}\"]"));
+            let (config, diagnostics) = Config::check(&with_mod_key(&uncommented));
+            assert_eq!(diagnostics.len(), Config::check(&with_mod_key(example)).1.len(), "{mod_key}");
+            assert_eq!(config.keys.iter().filter(|key| key.action == "swap_tags" || key.action == "merge_tags").count(), 2 * 9);
+        }
+    }
+
+    #[test]
+    fn key_spec_parse() {
+        let specs = [
+            ("Alt+Shift+Return", MOD_ALT | MOD_SHIFT, VK_RETURN),
+            ("Win+F5", MOD_WIN, VK_F5),
+            ("Ctrl+Alt+Left", MOD_CONTROL | MOD_ALT, VK_LEFT),
+            ("Alt+Oem_Comma", MOD_ALT, VK_OEM_COMMA),
+            ("Ctrl+Shift+Win+F24", MOD_CONTROL | MOD_SHIFT | MOD_WIN, VK_F24),
+            ("Space", HOT_KEY_MODIFIERS(0), VK_SPACE),
+            ("Alt+7", MOD_ALT, VK_7),
+            ("Alt+0xE9", MOD_ALT, VIRTUAL_KEY(0xE9)),
+        ];
+        for (text, modifiers, key) in specs {
+            let spec: KeySpec = text.parse().unwrap();
+            assert_eq!(spec, KeySpec::new(modifiers, key), "{text}");
+            assert_eq!(spec.to_string(), text);
+        }
+
+        assert_eq!("shift + ctrl + enter".parse::<KeySpec>().unwrap().to_string(), "Ctrl+Shift+Return");
+        assert_eq!(KeySpec::parse("Mod+q", MOD_WIN).unwrap(), KeySpec::new(MOD_WIN, VK_Q));
+        for text in ["", "Alt+", "Hyper+Q", "Alt+F25", "Alt+Comma"] {
+            assert!(text.parse::<KeySpec>().is_err(), "{text}");
+        }
+
+        let config = Config::parse("[[keys]]\nmodifiers = [\"Shift\"]\nkey = \"Mod+Ctrl+Oem_Comma\"\naction = \"zoom\"").unwrap();
+        assert_eq!(config.keys[0].spec().to_string(), "Ctrl+Alt+Shift+Oem_Comma");
+    }
+
+    #[test]
+    fn config_key_backend() {
+        let config = Config::parse(r#"
+            key_backend = "HotKey"
+
+            [[keys]]
+            key = "Win+L"
+            action = "zoom"
+            backend = "hook"
+
+            [[keys]]
+            key = "Alt+Q"
+            action = "quit"
+
+            [[tag_keys]]
+            modifiers = ["Win"]
+            action = "view"
+            backend = "auto"
+        "#).unwrap();
+        assert_eq!(config.key_backend, KeyBackend::HotKey);
+        assert_eq!(config.keys[0].backend, Some(KeyBackend::Hook));
+        assert_eq!(config.keys[1].backend, None);
+        assert_eq!(config.keys[2].backend, Some(KeyBackend::Auto));
+        assert_eq!(Config::default().key_backend, KeyBackend::Auto);
+
+        let (_, errors) = Config::check("[[keys]]\nkey = \"Win+L\"\naction = \"zoom\"\nbackend = \"driver\"");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line(), Some(1));
+    }
+
+    #[test]
+    fn config_modes_and_chords() {
+        let config = Config::parse(r##"
+            chord_timeout = 1500
+
+            [[keys]]
+            key = "Mod+R"
+            action = "enter_mode"
+            arg = "resize"
+
+            [[keys]]
+            key = "Mod+W, H"
+            action = "focus_monitor"
+            arg = 1
+
+            [[keys]]
+            key = "Mod+W, L"
+            action = "focus_monitor"
+            arg = -1
+
+            [[modes]]
+            name = "resize"
+            timeout = 5000
+
+            [[modes.keys]]
+            key = "H"
+            action = "set_monitor_factor"
+            arg = -0.05
+
+            [[modes.keys]]
+            key = "Return"
+            action = "exit_mode"
+        "##).unwrap();
+
+        assert_eq!(config.modes.len(), 2);
+        let resize = &config.modes[0];
+        assert_eq!((resize.name.as_str(), resize.timeout, resize.is_chord), ("resize", 5000, false));
+        assert_eq!(resize.keys.iter().map(|key| key.spec().to_string()).collect::<Vec<_>>(), vec!["H", "Return"]);
+
+        let chord = &config.modes[1];
+        assert_eq!((chord.name.as_str(), chord.timeout, chord.is_chord), ("Alt+W", 1500, true));
+        assert_eq!(chord.keys.iter().map(|key| key.spec().to_string()).collect::<Vec<_>>(), vec!["H", "L"]);
+
+        // Alt+R enters resize and a single Alt+W enters the chord.
+        let specs: Vec<String> = config.keys.iter().take(2).map(|key| key.spec().to_string()).collect();
+        assert_eq!(specs, vec!["Alt+R", "Alt+W"]);
+        assert_eq!(unsafe { config.keys[0].arg.unwrap().ui }, 0);
+        assert_eq!(unsafe { config.keys[1].arg.unwrap().ui }, 1);
+
+        let (_, diagnostics) = Config::check("[[keys]]\nkey = \"Mod+R\"\naction = \"enter_mode\"\narg = \"launch\"\n");
+        assert!(diagnostics[0].message().contains("unknown mode"));
+        let (_, diagnostics) = Config::check("[[modes]]\nname = \"m\"\n[[modes.keys]]\nkey = \"H\"\naction = \"zoom\"\n[[modes.keys]]\nkey = \"h\"\naction = \"quit\"\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line(), Some(6));
+    }
+
+    #[test]
+    fn config_spawn() {
+        let config = Config::parse(r##"
+            [[keys]]
+            key = "Mod+Return"
+            action = "spawn"
+            arg = "wt.exe"
+
+            [[keys]]
+            key = "Mod+Shift+Return"
+            action = "spawn"
+            arg = { program = "alacritty.exe", args = ["-e", "nvim"], cwd = "C:\\src", env = { EDITOR = "nvim" }, tags = [2], monitor = 1 }
+        "##).unwrap();
+
+        assert_eq!(config.spawns.len(), 2);
+        assert_eq!(unsafe { config.keys[1].arg.unwrap().ui }, 1);
+        assert!(!config.spawns[0].has_placement());
+
+        let command = &config.spawns[1];
+        assert_eq!(command.to_string(), "alacritty.exe -e nvim");
+        assert_eq!(command.cwd.as_deref(), Some("C:\\src"));
+        assert_eq!(command.env, vec![("EDITOR".to_string(), "nvim".to_string())]);
+        assert_eq!(command.tags, Some(0b10));
+        assert!(matches!(command.monitor, Some(MonitorTarget::Index(1))));
+
+        let errors = [
+            "[[keys]]\nkey = \"Q\"\naction = \"spawn\"\narg = 1",
+            "[[keys]]\nkey = \"Q\"\naction = \"spawn\"\narg = { args = [\"-e\"] }",
+            "[[keys]]\nkey = \"Q\"\naction = \"spawn\"\narg = { program = \"wt.exe\", tags = [12] }",
+        ];
+        for text in errors {
+            assert!(Config::parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn config_autostart() {
+        let config = Config::parse(r##"
+            [[autostart]]
+            program = "C:\\Program Files\\Slack\\slack"
+            tags = [4]
+
+            [[autostart]]
+            program = "wt.exe"
+            args = ["-p", "dev"]
+            on_reload = true
+            match = { title = { regex = "^dev" } }
+        "##).unwrap();
+
+        let slack = &config.autostart[0];
+        assert_eq!(slack.command.exe_name(), "slack.exe");
+        assert_eq!(slack.command.tags, Some(1 << 3));
+        assert!(slack.check_process && !slack.on_reload);
+        assert!(slack.guard.is_match(&Client{process_name: "Slack.exe".to_string(), ..Default::default()}));
+        assert!(!slack.guard.is_match(&Client{process_name: "slacker.exe".to_string(), ..Default::default()}));
+
+        let terminal = &config.autostart[1];
+        assert!(!terminal.check_process && terminal.on_reload);
+        assert!(terminal.guard.is_match(&Client{title: "dev - pwsh".to_string(), process_name: "WindowsTerminal.exe".to_string(), ..Default::default()}));
+        assert!(!terminal.guard.is_match(&Client{title: "pwsh".to_string(), ..Default::default()}));
+
+        assert!(Config::parse("[[autostart]]\nprogram = \"a.exe\"\nmatch = { name = \"a\" }").is_err());
+        assert!(Config::parse("[[autostart]]\nprogram = \"a.exe\"\nmatch = {}").is_err());
+    }
+
+    #[test]
+    fn config_macros() {
+        let config = Config::parse(r##"
+            [[keys]]
+            key = "Mod+M"
+            action = "run_macro"
+            arg = "stack-two"
+
+            [[macros]]
+            name = "stack-two"
+
+            [[macros.steps]]
+            action = "view"
+            arg = 2
+
+            [[macros.steps]]
+            action = "set_layout"
+            arg = "stack"
+            if_layout = "tile"
+
+            [[macros.steps]]
+            action = "focus_stack"
+            arg = 1
+            if_clients_over = 1
+            if_clients_under = 4
+        "##).unwrap();
+
+        assert_eq!(unsafe { config.keys[0].arg.unwrap().ui }, 0);
+        let steps = &config.macros[0].steps;
+        assert_eq!(steps.len(), 3);
+        assert_eq!(unsafe { steps[0].arg.unwrap().ui }, 1 << 1);
+
+        let tile = Layout::Tile(Default::default());
+        let stack = Layout::Stack(Default::default());
+        assert!(steps[0].condition.is_met(&stack, 0));
+        assert!(steps[1].condition.is_met(&tile, 0));
+        assert!(!steps[1].condition.is_met(&stack, 0));
+        assert!(!steps[2].condition.is_met(&tile, 1));
+        assert!(steps[2].condition.is_met(&tile, 2));
+        assert!(!steps[2].condition.is_met(&tile, 4));
+
+        let errors = [
+            "[[keys]]\nkey = \"Q\"\naction = \"run_macro\"\narg = \"missing\"",
+            "[[macros]]\nname = \"a\"\n[[macros.steps]]\naction = \"run_macro\"\narg = \"a\"",
+            "[[macros]]\nname = \"a\"\n[[macros.steps]]\naction = \"zoom\"\nif_layout = \"monocle\"",
+        ];
+        for text in errors {
+            assert!(Config::parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn config_passthrough() {
+        let config = Config::parse(r#"
+            [[passthrough]]
+            process_name = { exact = "mstsc.exe", ignore_case = true }
+
+            [[passthrough]]
+            title = { regex = "^Factorio" }
+            keys = ["Mod+J", "Alt+Tab"]
+
+            [[keys]]
+            key = "Mod+Pause"
+            action = "toggle_passthrough"
+        "#).unwrap();
+        assert_eq!(config.keys[0].action, "toggle_passthrough");
+        assert_eq!(config.keys[1].action, "view");
+
+        let remote_desktop = Client{process_name: "MSTSC.EXE".to_string(), ..Default::default()};
+        let game = Client{title: "Factorio 1.1".to_string(), ..Default::default()};
+        assert!(config.passthrough[0].rule.is_match(&remote_desktop));
+        assert!(!config.passthrough[0].rule.is_match(&game));
+        assert!(config.passthrough[1].rule.is_match(&game));
+
+        assert!(config.passthrough[0].suspends(&"Alt+Q".parse().unwrap()));
+        assert!(config.passthrough[1].suspends(&"Alt+J".parse().unwrap()));
+        assert!(config.passthrough[1].suspends(&"Alt+Tab".parse().unwrap()));
+        assert!(!config.passthrough[1].suspends(&"Alt+K".parse().unwrap()));
+
+        for text in ["[[passthrough]]\nkeys = [\"Alt+J\"]", "[[passthrough]]\nclass = \"Game\"\nkeys = [\"Hyper+J\"]"] {
+            assert!(Config::parse(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn config_themes() {
+        let config = Config::parse(r##"
+            theme = "nord"
+
+            [[keys]]
+            key = "Mod+F12"
+            action = "set_theme"
+            arg = "default"
+
+            [[themes]]
+            name = "nord"
+            normal = { text = "#d8dee9", background = "#2e3440" }
+            occupied = { text = "#88c0d0" }
+
+            [[themes]]
+            name = "light"
+            selected = { background = "#5e81ac80" }
+        "##).unwrap();
+
+        assert_eq!(config.themes.iter().map(|theme| theme.name.as_str()).collect::<Vec<_>>(), vec![THEME, "nord", "light"]);
+        assert_eq!(config.theme, 1);
+        assert_eq!(unsafe { config.keys[0].arg.unwrap().ui }, 0);
+
+        let nord = &config.themes[1];
+        assert_eq!(nord.normal.background.r, 0x2e as f32 / 255.0);
+        assert_eq!(nord.occupied.text.g, 0xc0 as f32 / 255.0);
+        // Colors a theme leaves out come from the built-in theme.
+        assert_eq!(nord.occupied.background.r, BAR_COLOR_BACKGROUND.r);
+        assert_eq!(nord.urgent.background.r, BAR_COLOR_URGENT_BOX.r);
+        assert_eq!(config.themes[2].selected.background.a, 0x80 as f32 / 255.0);
+
+        let (config, errors) = Config::check("theme = \"missing\"\n[[themes]]\nname = \"dark\"\nstatus = { text = \"#12345\" }");
+        assert_eq!(config.theme, 0);
+        assert_eq!(errors.iter().map(|error| error.line()).collect::<Vec<_>>(), vec![Some(4), Some(1)]);
+    }
 }